@@ -2,138 +2,112 @@
 //!
 //! This module provides functionality to detect what type of application
 //! is running based on process information (command line, name, etc.).
+//! Detection is driven by the rule table in [`rules`] and refined with the
+//! package manifests of the project by [`manifest`]; the runtime version is
+//! read from the executable path by [`runtime`].
 
-use crate::models::{AppType, ProcessInfo};
-
-/// Detects the application type from process information.
-///
-/// Uses pattern matching on the command line and process name
-/// to identify common development frameworks and servers.
-pub fn detect_app_type(process: &ProcessInfo) -> AppType {
-    // Combine name and command for matching
-    let name_lower = process.name.to_lowercase();
-    let command_lower = process
-        .command
-        .as_ref()
-        .map(|c| c.to_lowercase())
-        .unwrap_or_default();
-
-    // Check patterns in order of specificity
-
-    // Node.js patterns
-    if name_lower.contains("node")
-        || command_lower.contains("node ")
-        || command_lower.contains("npm ")
-        || command_lower.contains("yarn ")
-        || command_lower.contains("npx ")
-        || command_lower.contains("next ")
-        || command_lower.contains("react-scripts")
-        || command_lower.contains("vite")
-        || command_lower.contains("webpack")
-    {
-        return AppType::NodeJs;
-    }
-
-    // Python patterns
-    if name_lower.contains("python")
-        || command_lower.contains("python ")
-        || command_lower.contains("python3 ")
-        || command_lower.contains("uvicorn ")
-        || command_lower.contains("gunicorn ")
-        || command_lower.contains("flask ")
-        || command_lower.contains("django")
-        || command_lower.contains("fastapi")
-    {
-        return AppType::Python;
-    }
-
-    // .NET patterns
-    if name_lower.contains("dotnet")
-        || command_lower.contains("dotnet ")
-        || command_lower.ends_with(".dll")
-        || command_lower.contains(".dll ")
-    {
-        return AppType::DotNet;
-    }
-
-    // Java patterns
-    if name_lower.contains("java")
-        || command_lower.contains("java ")
-        || command_lower.ends_with(".jar")
-        || command_lower.contains(".jar ")
-        || command_lower.contains("spring")
-        || command_lower.contains("tomcat")
-        || command_lower.contains("jetty")
-    {
-        return AppType::Java;
-    }
-
-    // Rust patterns (must be before Go since "cargo run" contains "go run")
-    if command_lower.contains("cargo run")
-        || command_lower.contains("/target/debug/")
-        || command_lower.contains("/target/release/")
-    {
-        return AppType::Rust;
-    }
-
-    // Go patterns
-    if command_lower.contains("go run")
-        || command_lower.contains("gin")
-        || command_lower.contains("echo")
-        || command_lower.contains("fiber")
-    {
-        return AppType::Go;
-    }
-
-    // Ruby patterns
-    if name_lower.contains("ruby")
-        || command_lower.contains("ruby ")
-        || command_lower.contains("rails ")
-        || command_lower.contains("puma ")
-        || command_lower.contains("unicorn ")
-        || command_lower.contains("bundle exec")
-    {
-        return AppType::Ruby;
-    }
-
-    // PHP patterns
-    if name_lower.contains("php")
-        || command_lower.contains("php ")
-        || command_lower.contains("artisan ")
-        || command_lower.contains("laravel")
-    {
-        return AppType::Php;
-    }
-
-    // Nginx patterns
-    if name_lower.contains("nginx") || command_lower.contains("nginx") {
-        return AppType::Nginx;
-    }
-
-    // Apache patterns
-    if name_lower.contains("httpd")
-        || name_lower.contains("apache")
-        || command_lower.contains("httpd")
-        || command_lower.contains("apache")
-    {
-        return AppType::Apache;
-    }
-
-    AppType::Unknown
+mod manifest;
+mod rules;
+mod runtime;
+
+use crate::models::ProcessInfo;
+
+pub use manifest::ManifestCache;
+pub use rules::{Glob, RuleMatch, RuleSet, RULES_FILE_NAME};
+pub use runtime::runtime_version;
+
+/// Lowercased view of a process command line used for matching.
+struct CommandLine {
+    /// Process name
+    name: String,
+    /// File name of `argv[0]` (or of the executable when argv is unknown)
+    program: String,
+    /// Paths the process was started from: `argv[0]` and the executable
+    executables: Vec<String>,
+    /// Arguments after `argv[0]`
+    args: Vec<String>,
+    /// The full command line
+    line: String,
+}
+
+impl CommandLine {
+    fn new(process: &ProcessInfo) -> Self {
+        let lower = |s: &str| s.to_lowercase();
+        let exe = process
+            .exe
+            .as_ref()
+            .map(|exe| lower(&exe.to_string_lossy()));
+
+        let mut executables: Vec<String> =
+            process.argv.first().map(|a| lower(a)).into_iter().collect();
+        executables.extend(exe.clone());
+
+        let program = process
+            .argv
+            .first()
+            .map(|a| lower(a))
+            .or(exe)
+            .map(|path| file_name(&path).to_string())
+            .unwrap_or_else(|| lower(&process.name));
+
+        let args = process.argv.iter().skip(1).map(|a| lower(a)).collect();
+
+        let line = match &process.command {
+            Some(command) => lower(command),
+            None => lower(&process.argv.join(" ")),
+        };
+
+        Self {
+            name: lower(&process.name),
+            program,
+            executables,
+            args,
+            line,
+        }
+    }
+
+    /// Returns the first argument after the program, e.g. a subcommand.
+    fn first_arg(&self) -> Option<&str> {
+        self.args.first().map(String::as_str)
+    }
+
+    /// Returns true if any argument after the program matches.
+    fn has_arg(&self, predicate: impl Fn(&str) -> bool) -> bool {
+        self.args.iter().any(|a| predicate(a))
+    }
+}
+
+/// Returns the last path component of `path`.
+fn file_name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::AppType;
+
+    /// Detects the application type of a process with the built-in rules.
+    fn detect_app_type(process: &ProcessInfo) -> AppType {
+        RuleSet::builtin().detect(process).app_type
+    }
 
     fn make_process(name: &str, command: Option<&str>) -> ProcessInfo {
         let mut p = ProcessInfo::new(1234, name);
         if let Some(cmd) = command {
             p.command = Some(cmd.to_string());
+            p.argv = cmd.split_whitespace().map(str::to_string).collect();
         }
         p
     }
 
+    fn make_process_with_argv(name: &str, argv: &[&str]) -> ProcessInfo {
+        let mut p = ProcessInfo::new(1234, name);
+        p.argv = argv.iter().map(|a| a.to_string()).collect();
+        p.command = Some(argv.join(" "));
+        p
+    }
+
     #[test]
     fn test_detect_nodejs() {
         let process = make_process("node", Some("node server.js"));
@@ -181,4 +155,134 @@ mod tests {
         let process = make_process("someapp", Some("/usr/bin/someapp"));
         assert_eq!(detect_app_type(&process), AppType::Unknown);
     }
+
+    #[test]
+    fn test_detect_from_argv0_path() {
+        let process = make_process_with_argv("MainThread", &["/usr/local/bin/node", "server.js"]);
+        assert_eq!(detect_app_type(&process), AppType::NodeJs);
+
+        let process = make_process_with_argv(
+            "ruby",
+            &["/Users/me/.rbenv/shims/bundle", "exec", "rails", "s"],
+        );
+        assert_eq!(detect_app_type(&process), AppType::Ruby);
+    }
+
+    #[test]
+    fn test_detect_script_path_with_spaces() {
+        let process = make_process_with_argv(
+            "MyApp",
+            &["/opt/My Company/dotnet", "/opt/My Company/Api Server.dll"],
+        );
+        assert_eq!(detect_app_type(&process), AppType::DotNet);
+    }
+
+    #[test]
+    fn test_detect_rust_from_exe() {
+        let mut process = ProcessInfo::new(1234, "api");
+        process.exe = Some("/home/me/api/target/release/api".into());
+        assert_eq!(detect_app_type(&process), AppType::Rust);
+    }
+
+    #[test]
+    fn test_detect_subcommand_must_be_first_argument() {
+        // "run" as a later argument is not `cargo run`
+        let process = make_process_with_argv("cargo", &["cargo", "build", "--bin", "run"]);
+        assert_eq!(detect_app_type(&process), AppType::Unknown);
+    }
+
+    #[test]
+    fn test_detect_databases() {
+        let cases = [
+            ("postgres", "/usr/lib/postgresql/16/bin/postgres -D /var/lib/postgresql/16/main", AppType::PostgreSql),
+            ("mysqld", "/usr/sbin/mysqld", AppType::MySql),
+            ("redis-server", "redis-server *:6379", AppType::Redis),
+            ("mongod", "mongod --config /etc/mongod.conf", AppType::MongoDb),
+            ("memcached", "memcached -p 11211", AppType::Memcached),
+            (
+                "java",
+                "/usr/share/elasticsearch/jdk/bin/java -Xms1g org.elasticsearch.bootstrap.Elasticsearch",
+                AppType::Elasticsearch,
+            ),
+        ];
+
+        for (name, command, expected) in cases {
+            let process = make_process(name, Some(command));
+            assert_eq!(detect_app_type(&process), expected, "{}", command);
+        }
+    }
+
+    #[test]
+    fn test_detect_brokers() {
+        let process = make_process(
+            "java",
+            Some("java -Xmx1G -cp /opt/kafka/libs/* kafka.Kafka config/server.properties"),
+        );
+        assert_eq!(detect_app_type(&process), AppType::Kafka);
+
+        let process = make_process(
+            "beam.smp",
+            Some("/usr/lib/erlang/erts-14.2/bin/beam.smp -- -root /usr/lib/erlang -s rabbit boot"),
+        );
+        assert_eq!(detect_app_type(&process), AppType::RabbitMq);
+
+        // Other BEAM applications
+        let process = make_process(
+            "beam.smp",
+            Some("/usr/lib/erlang/erts-14.2/bin/beam.smp -- -root /usr/lib/erlang -extra mix phx.server"),
+        );
+        assert_eq!(detect_app_type(&process), AppType::Elixir);
+    }
+
+    #[test]
+    fn test_detect_proxies_and_runtimes() {
+        let cases = [
+            ("caddy", "caddy run --config Caddyfile", AppType::Caddy),
+            (
+                "traefik",
+                "traefik --configfile=/etc/traefik.yml",
+                AppType::Traefik,
+            ),
+            (
+                "haproxy",
+                "haproxy -f /etc/haproxy/haproxy.cfg",
+                AppType::HaProxy,
+            ),
+            (
+                "docker-proxy",
+                "/usr/bin/docker-proxy -proto tcp -host-ip 0.0.0.0 -host-port 5432",
+                AppType::DockerProxy,
+            ),
+            ("deno", "deno run --allow-net server.ts", AppType::Deno),
+            ("bun", "bun run dev", AppType::Bun),
+        ];
+
+        for (name, command, expected) in cases {
+            let process = make_process(name, Some(command));
+            assert_eq!(detect_app_type(&process), expected, "{}", command);
+        }
+    }
+
+    #[test]
+    fn test_detect_ide_helpers() {
+        let process = make_process_with_argv(
+            "node",
+            &[
+                "/home/me/.vscode-server/bin/0ee08df0cf/node",
+                "/home/me/.vscode-server/bin/0ee08df0cf/out/server-main.js",
+            ],
+        );
+        assert_eq!(detect_app_type(&process), AppType::VsCode);
+
+        let mut process = make_process_with_argv(
+            "java",
+            &["/opt/idea/jbr/bin/java", "com.intellij.idea.Main"],
+        );
+        assert_eq!(detect_app_type(&process), AppType::JetBrains);
+
+        process.argv = vec!["java".to_string()];
+        process.exe =
+            Some("/Applications/IntelliJ IDEA.app/Contents/jbr/Contents/Home/bin/java".into());
+        assert_eq!(detect_app_type(&process), AppType::JetBrains);
+    }
 }
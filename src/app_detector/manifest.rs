@@ -0,0 +1,585 @@
+//! Manifest-based detection.
+//!
+//! The command line says which runtime a process uses, but the project it
+//! runs in says what it is built with. This module reads the package
+//! manifests in the working directory and project root of a process
+//! (`package.json`, `pyproject.toml`, `requirements.txt`, `Gemfile`,
+//! `*.csproj`, `pom.xml`, `build.gradle`, `go.mod`, `Cargo.toml`) and uses
+//! them to confirm or correct the rule-based detection and to identify the
+//! framework.
+
+use crate::models::{AppType, Confidence, Detection, ProcessInfo};
+use regex::Regex;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Node.js packages identifying a framework, most specific first.
+const NODE_FRAMEWORKS: &[(&str, &str)] = &[
+    ("next", "Next.js"),
+    ("nuxt", "Nuxt"),
+    ("@remix-run/dev", "Remix"),
+    ("@sveltejs/kit", "SvelteKit"),
+    ("astro", "Astro"),
+    ("gatsby", "Gatsby"),
+    ("@angular/core", "Angular"),
+    ("@nestjs/core", "NestJS"),
+    ("storybook", "Storybook"),
+    ("vite", "Vite"),
+    ("fastify", "Fastify"),
+    ("koa", "Koa"),
+    ("express", "Express"),
+];
+
+/// Commands used in `package.json` scripts that identify a framework.
+const NODE_SCRIPT_COMMANDS: &[(&str, &str)] = &[
+    ("next", "Next.js"),
+    ("nuxt", "Nuxt"),
+    ("nuxi", "Nuxt"),
+    ("remix", "Remix"),
+    ("astro", "Astro"),
+    ("gatsby", "Gatsby"),
+    ("ng", "Angular"),
+    ("nest", "NestJS"),
+    ("storybook", "Storybook"),
+    ("start-storybook", "Storybook"),
+    ("vite", "Vite"),
+];
+
+/// Python packages identifying a framework.
+const PYTHON_FRAMEWORKS: &[(&str, &str)] = &[
+    ("django", "Django"),
+    ("fastapi", "FastAPI"),
+    ("flask", "Flask"),
+    ("starlette", "Starlette"),
+    ("aiohttp", "aiohttp"),
+];
+
+/// Ruby gems identifying a framework.
+const RUBY_FRAMEWORKS: &[(&str, &str)] = &[
+    ("rails", "Rails"),
+    ("hanami", "Hanami"),
+    ("sinatra", "Sinatra"),
+];
+
+/// Go modules identifying a framework.
+const GO_FRAMEWORKS: &[(&str, &str)] = &[
+    ("github.com/gin-gonic/gin", "Gin"),
+    ("github.com/labstack/echo", "Echo"),
+    ("github.com/gofiber/fiber", "Fiber"),
+    ("github.com/go-chi/chi", "chi"),
+];
+
+/// Rust crates identifying a framework.
+const RUST_FRAMEWORKS: &[(&str, &str)] = &[
+    ("axum", "Axum"),
+    ("actix-web", "Actix Web"),
+    ("rocket", "Rocket"),
+    ("warp", "Warp"),
+];
+
+/// What a manifest says about the project it belongs to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestMatch {
+    /// File name of the manifest
+    pub manifest: String,
+    /// Application type of the project
+    pub app_type: AppType,
+    /// Framework declared as a dependency
+    pub framework: Option<String>,
+    /// Frameworks started by `package.json` scripts, by script name
+    scripts: HashMap<String, String>,
+}
+
+impl ManifestMatch {
+    fn new(manifest: &str, app_type: AppType, framework: Option<&str>) -> Self {
+        Self {
+            manifest: manifest.to_string(),
+            app_type,
+            framework: framework.map(str::to_string),
+            scripts: HashMap::new(),
+        }
+    }
+
+    /// Returns the framework of a process started as a package script
+    /// (`npm run dev`, `yarn dev`, `pnpm dev`).
+    fn script_framework(&self, process: &ProcessInfo) -> Option<&str> {
+        let program = process.argv.first()?;
+        let program = program.rsplit('/').next().unwrap_or(program);
+        if !matches!(program, "npm" | "yarn" | "pnpm" | "bun") {
+            return None;
+        }
+
+        let mut args = process.argv.iter().skip(1).map(String::as_str);
+        let script = match args.next()? {
+            "run" | "run-script" => args.next()?,
+            script => script,
+        };
+        self.scripts.get(script).map(String::as_str)
+    }
+}
+
+/// Reads manifests and refines detections, examining each directory only
+/// once.
+///
+/// Keeping one cache across scans avoids re-reading the manifests of
+/// long-running processes.
+#[derive(Debug, Default)]
+pub struct ManifestCache {
+    dirs: HashMap<PathBuf, Vec<ManifestMatch>>,
+}
+
+impl ManifestCache {
+    /// Creates an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns what the manifests in `dir` say about the project.
+    pub fn inspect(&mut self, dir: &Path) -> &[ManifestMatch] {
+        self.dirs
+            .entry(dir.to_path_buf())
+            .or_insert_with(|| inspect_dir(dir))
+    }
+
+    /// Confirms or corrects a rule-based detection with the manifests in
+    /// the working directory and project root of the process.
+    ///
+    /// - A manifest for the detected application type confirms it: the
+    ///   confidence becomes high and a missing framework is filled in.
+    /// - A low-confidence or unknown detection is replaced by the project
+    ///   type, but only for processes running an executable from the
+    ///   project (e.g. a compiled Go or Rust binary). A database started
+    ///   from a project directory is not part of that project.
+    /// - A medium or high confidence detection for another type is kept;
+    ///   the runtime on the command line wins over the project.
+    pub fn refine(&mut self, process: &ProcessInfo, detection: Detection) -> Detection {
+        let mut dirs: Vec<&Path> = Vec::new();
+        for dir in [process.cwd.as_deref(), process.project_dir.as_deref()]
+            .into_iter()
+            .flatten()
+        {
+            if !dirs.contains(&dir) {
+                dirs.push(dir);
+            }
+        }
+
+        let mut matches: Vec<(&Path, ManifestMatch)> = Vec::new();
+        for dir in dirs {
+            matches.extend(self.inspect(dir).iter().map(|m| (dir, m.clone())));
+        }
+
+        if let Some((_, found)) = matches
+            .iter()
+            .find(|(_, m)| m.app_type == detection.app_type)
+        {
+            let framework = detection
+                .framework
+                .clone()
+                .or_else(|| found.script_framework(process).map(str::to_string))
+                .or_else(|| found.framework.clone());
+            return Detection {
+                framework,
+                confidence: Confidence::High,
+                manifest: Some(found.manifest.clone()),
+                ..detection
+            };
+        }
+
+        if detection.confidence < Confidence::Medium {
+            if let Some((_, found)) = matches.iter().find(|(dir, _)| runs_from(process, dir)) {
+                return Detection {
+                    app_type: found.app_type.clone(),
+                    framework: found.framework.clone(),
+                    confidence: Confidence::Medium,
+                    matched_rule: None,
+                    manifest: Some(found.manifest.clone()),
+                };
+            }
+        }
+
+        detection
+    }
+}
+
+/// Returns true if the process runs an executable located under `dir`.
+fn runs_from(process: &ProcessInfo, dir: &Path) -> bool {
+    let argv0 = process
+        .argv
+        .first()
+        .filter(|a| a.contains('/'))
+        .map(|a| match &process.cwd {
+            Some(cwd) => cwd.join(a),
+            None => PathBuf::from(a),
+        });
+
+    process
+        .exe
+        .iter()
+        .chain(argv0.iter())
+        .any(|path| path.starts_with(dir))
+}
+
+/// Reads every known manifest in `dir`.
+fn inspect_dir(dir: &Path) -> Vec<ManifestMatch> {
+    let read = |file: &str| fs::read_to_string(dir.join(file)).ok();
+    let mut matches = Vec::new();
+
+    if let Some(content) = read("package.json") {
+        matches.extend(parse_package_json(&content));
+    }
+    for file in ["pyproject.toml", "requirements.txt"] {
+        if let Some(content) = read(file) {
+            let packages = if file.ends_with(".toml") {
+                pyproject_dependencies(&content)
+            } else {
+                requirements_dependencies(&content)
+            };
+            let framework =
+                find_framework(PYTHON_FRAMEWORKS, |name| packages.iter().any(|p| p == name));
+            matches.push(ManifestMatch::new(file, AppType::Python, framework));
+        }
+    }
+    if let Some(content) = read("Gemfile") {
+        let gems = gemfile_dependencies(&content);
+        let framework = find_framework(RUBY_FRAMEWORKS, |name| gems.iter().any(|g| g == name));
+        matches.push(ManifestMatch::new("Gemfile", AppType::Ruby, framework));
+    }
+    if let Some((file, content)) = read_csproj(dir) {
+        let framework = content
+            .contains("Microsoft.NET.Sdk.Web")
+            .then_some("ASP.NET");
+        matches.push(ManifestMatch::new(&file, AppType::DotNet, framework));
+    }
+    for file in ["pom.xml", "build.gradle", "build.gradle.kts"] {
+        if let Some(content) = read(file) {
+            let framework = (content.contains("spring-boot")
+                || content.contains("org.springframework.boot"))
+            .then_some("Spring Boot");
+            matches.push(ManifestMatch::new(file, AppType::Java, framework));
+        }
+    }
+    if let Some(content) = read("go.mod") {
+        let framework = find_framework(GO_FRAMEWORKS, |module| {
+            content.split_whitespace().any(|word| {
+                word == module
+                    || word
+                        .strip_prefix(module)
+                        .is_some_and(|v| v.starts_with("/v"))
+            })
+        });
+        matches.push(ManifestMatch::new("go.mod", AppType::Go, framework));
+    }
+    if let Some(content) = read("Cargo.toml") {
+        let crates = cargo_dependencies(&content);
+        let framework = find_framework(RUST_FRAMEWORKS, |name| crates.iter().any(|c| c == name));
+        matches.push(ManifestMatch::new("Cargo.toml", AppType::Rust, framework));
+    }
+
+    matches
+}
+
+/// Returns the first framework whose package is present.
+fn find_framework(
+    frameworks: &[(&str, &'static str)],
+    has_package: impl Fn(&str) -> bool,
+) -> Option<&'static str> {
+    frameworks
+        .iter()
+        .find(|(package, _)| has_package(package))
+        .map(|(_, framework)| *framework)
+}
+
+/// Reads `package.json`: dependencies and scripts.
+fn parse_package_json(content: &str) -> Option<ManifestMatch> {
+    let json: serde_json::Value = serde_json::from_str(content).ok()?;
+
+    let has_dependency = |name: &str| {
+        ["dependencies", "devDependencies"]
+            .iter()
+            .any(|key| json.get(key).and_then(|deps| deps.get(name)).is_some())
+    };
+    let framework = find_framework(NODE_FRAMEWORKS, has_dependency);
+
+    let mut found = ManifestMatch::new("package.json", AppType::NodeJs, framework);
+    if let Some(scripts) = json.get("scripts").and_then(|s| s.as_object()) {
+        for (name, command) in scripts {
+            let Some(command) = command.as_str() else {
+                continue;
+            };
+            let program = command.split_whitespace().next().unwrap_or_default();
+            if let Some(framework) = find_framework(NODE_SCRIPT_COMMANDS, |c| c == program) {
+                found.scripts.insert(name.clone(), framework.to_string());
+            }
+        }
+    }
+    Some(found)
+}
+
+/// Returns the normalized dependency names of `pyproject.toml`, from PEP
+/// 621 `[project]` and Poetry sections.
+fn pyproject_dependencies(content: &str) -> Vec<String> {
+    let Ok(toml) = content.parse::<toml::Table>() else {
+        return Vec::new();
+    };
+
+    let mut names = Vec::new();
+    let project_deps = toml
+        .get("project")
+        .and_then(|p| p.get("dependencies"))
+        .and_then(|d| d.as_array());
+    for spec in project_deps.into_iter().flatten() {
+        names.extend(spec.as_str().and_then(requirement_name));
+    }
+
+    let poetry_deps = toml
+        .get("tool")
+        .and_then(|t| t.get("poetry"))
+        .and_then(|p| p.get("dependencies"))
+        .and_then(|d| d.as_table());
+    for name in poetry_deps.into_iter().flat_map(|deps| deps.keys()) {
+        names.push(name.to_lowercase());
+    }
+
+    names
+}
+
+/// Returns the normalized package names of `requirements.txt`.
+fn requirements_dependencies(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .filter(|line| !line.starts_with('-'))
+        .filter_map(requirement_name)
+        .collect()
+}
+
+/// Returns the lowercased package name of a requirement such as
+/// `Django>=4.2` or `uvicorn[standard]`.
+fn requirement_name(spec: &str) -> Option<String> {
+    let name: String = spec
+        .trim()
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        .collect();
+    (!name.is_empty()).then(|| name.to_lowercase())
+}
+
+/// Returns the gem names declared in a `Gemfile`.
+fn gemfile_dependencies(content: &str) -> Vec<String> {
+    static GEM: OnceLock<Regex> = OnceLock::new();
+    let gem = GEM.get_or_init(|| {
+        Regex::new(r#"(?m)^\s*gem\s+['"]([^'"]+)['"]"#).expect("gem pattern is valid")
+    });
+
+    gem.captures_iter(content)
+        .map(|captures| captures[1].to_string())
+        .collect()
+}
+
+/// Returns the crate names in the dependency tables of `Cargo.toml`.
+fn cargo_dependencies(content: &str) -> Vec<String> {
+    let Ok(toml) = content.parse::<toml::Table>() else {
+        return Vec::new();
+    };
+
+    ["dependencies", "dev-dependencies"]
+        .iter()
+        .filter_map(|key| toml.get(*key).and_then(|deps| deps.as_table()))
+        .flat_map(|deps| deps.keys().cloned())
+        .collect()
+}
+
+/// Reads the first `*.csproj` file in `dir` (in file name order).
+fn read_csproj(dir: &Path) -> Option<(String, String)> {
+    let mut projects: Vec<PathBuf> = fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "csproj"))
+        .collect();
+    projects.sort();
+
+    let path = projects.into_iter().next()?;
+    let content = fs::read_to_string(&path).ok()?;
+    let file = path.file_name()?.to_string_lossy().into_owned();
+    Some((file, content))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates a temporary project tree from `(path, content)` pairs.
+    fn project(files: &[(&str, &str)]) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for (file, content) in files {
+            let path = dir.path().join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        dir
+    }
+
+    fn process(dir: &Path, argv: &[&str]) -> ProcessInfo {
+        let mut process = ProcessInfo::new(1, "test");
+        process.argv = argv.iter().map(|a| a.to_string()).collect();
+        process.cwd = Some(dir.to_path_buf());
+        process.project_dir = Some(dir.to_path_buf());
+        process
+    }
+
+    fn detection(app_type: AppType, confidence: Confidence) -> Detection {
+        Detection {
+            app_type,
+            confidence,
+            matched_rule: Some("test-rule".to_string()),
+            ..Detection::unknown()
+        }
+    }
+
+    #[test]
+    fn test_inspect_manifests() {
+        let dir = project(&[
+            (
+                "package.json",
+                r#"{"dependencies": {"express": "^4"}, "devDependencies": {"vite": "^5"}}"#,
+            ),
+            ("requirements.txt", "# web\nDjango>=4.2\n-r dev.txt\n"),
+            (
+                "Gemfile",
+                "source 'https://rubygems.org'\ngem \"rails\", \"~> 7.1\"\n",
+            ),
+            (
+                "Api.csproj",
+                r#"<Project Sdk="Microsoft.NET.Sdk.Web"></Project>"#,
+            ),
+            (
+                "go.mod",
+                "module x\n\nrequire github.com/labstack/echo/v4 v4.11.4\n",
+            ),
+            (
+                "Cargo.toml",
+                "[package]\nname = \"x\"\n\n[dependencies]\naxum = \"0.7\"\n",
+            ),
+        ]);
+
+        let mut cache = ManifestCache::new();
+        let found: Vec<(String, AppType, Option<String>)> = cache
+            .inspect(dir.path())
+            .iter()
+            .map(|m| (m.manifest.clone(), m.app_type.clone(), m.framework.clone()))
+            .collect();
+
+        let expected = [
+            ("package.json", AppType::NodeJs, "Vite"),
+            ("requirements.txt", AppType::Python, "Django"),
+            ("Gemfile", AppType::Ruby, "Rails"),
+            ("Api.csproj", AppType::DotNet, "ASP.NET"),
+            ("go.mod", AppType::Go, "Echo"),
+            ("Cargo.toml", AppType::Rust, "Axum"),
+        ];
+        let expected: Vec<(String, AppType, Option<String>)> = expected
+            .into_iter()
+            .map(|(m, t, f)| (m.to_string(), t, Some(f.to_string())))
+            .collect();
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn test_pyproject_and_gradle() {
+        let dir = project(&[
+            (
+                "pyproject.toml",
+                "[project]\nname = \"api\"\ndependencies = [\"fastapi>=0.110\", \"uvicorn[standard]\"]\n",
+            ),
+            (
+                "build.gradle.kts",
+                "plugins { id(\"org.springframework.boot\") version \"3.2.0\" }\n",
+            ),
+        ]);
+
+        let mut cache = ManifestCache::new();
+        let found = cache.inspect(dir.path());
+
+        assert_eq!(found[0].framework.as_deref(), Some("FastAPI"));
+        assert_eq!(found[1].app_type, AppType::Java);
+        assert_eq!(found[1].framework.as_deref(), Some("Spring Boot"));
+    }
+
+    #[test]
+    fn test_confirm_detection_and_framework_from_script() {
+        let dir = project(&[(
+            "package.json",
+            r#"{"scripts": {"dev": "next dev -p 3000", "storybook": "storybook dev"},
+                "dependencies": {"next": "14", "express": "4"}}"#,
+        )]);
+        let mut cache = ManifestCache::new();
+
+        let refined = cache.refine(
+            &process(dir.path(), &["npm", "run", "storybook"]),
+            detection(AppType::NodeJs, Confidence::Medium),
+        );
+        assert_eq!(refined.app_type, AppType::NodeJs);
+        assert_eq!(refined.framework.as_deref(), Some("Storybook"));
+        assert_eq!(refined.confidence, Confidence::High);
+        assert_eq!(refined.manifest.as_deref(), Some("package.json"));
+        assert_eq!(refined.matched_rule.as_deref(), Some("test-rule"));
+
+        let refined = cache.refine(
+            &process(dir.path(), &["node", "server.js"]),
+            detection(AppType::NodeJs, Confidence::High),
+        );
+        assert_eq!(refined.framework.as_deref(), Some("Next.js"));
+    }
+
+    #[test]
+    fn test_correct_unknown_project_binary() {
+        let dir = project(&[(
+            "go.mod",
+            "module api\n\nrequire (\n\tgithub.com/gin-gonic/gin v1.9.1\n)\n",
+        )]);
+        let mut cache = ManifestCache::new();
+
+        let refined = cache.refine(&process(dir.path(), &["./bin/api"]), Detection::unknown());
+        assert_eq!(refined.app_type, AppType::Go);
+        assert_eq!(refined.framework.as_deref(), Some("Gin"));
+        assert_eq!(refined.confidence, Confidence::Medium);
+        assert_eq!(refined.matched_rule, None);
+        assert_eq!(refined.manifest.as_deref(), Some("go.mod"));
+    }
+
+    #[test]
+    fn test_keep_detection_of_unrelated_process() {
+        let dir = project(&[("package.json", r#"{"dependencies": {"next": "14"}}"#)]);
+        let mut cache = ManifestCache::new();
+
+        // A database started from the project directory
+        let refined = cache.refine(
+            &process(dir.path(), &["/usr/bin/redis-server", "*:6379"]),
+            Detection::unknown(),
+        );
+        assert_eq!(refined, Detection::unknown());
+
+        // The runtime on the command line wins over the project type
+        let python = detection(AppType::Python, Confidence::High);
+        let refined = cache.refine(
+            &process(dir.path(), &["python3", "-m", "http.server"]),
+            python.clone(),
+        );
+        assert_eq!(refined, python);
+    }
+
+    #[test]
+    fn test_directories_are_cached() {
+        let dir = project(&[("Gemfile", "gem 'sinatra'\n")]);
+        let mut cache = ManifestCache::new();
+        assert_eq!(
+            cache.inspect(dir.path())[0].framework.as_deref(),
+            Some("Sinatra")
+        );
+
+        fs::remove_file(dir.path().join("Gemfile")).unwrap();
+        assert_eq!(cache.inspect(dir.path()).len(), 1);
+    }
+}
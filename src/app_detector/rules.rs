@@ -0,0 +1,684 @@
+//! Detection rule table.
+//!
+//! Application types are detected by an ordered table of rules. The
+//! built-in rules are embedded from `default_rules.toml`; users can add,
+//! override or disable rules in `~/.config/ports/rules.toml`:
+//!
+//! ```toml
+//! # Turn off built-in rules by name
+//! disable = ["go-frameworks"]
+//!
+//! # A rule with the name of a built-in rule replaces it, any other rule is
+//! # checked before the built-in rules
+//! [[rule]]
+//! name = "gleam"
+//! app_type = "Gleam"
+//! program = ["beam.smp"]
+//!
+//! # A rule may also name the framework it identifies
+//! [[rule]]
+//! name = "remix"
+//! app_type = "NodeJs"
+//! framework = "Remix"
+//! confidence = "medium"
+//! arg = ["remix", "*/remix"]
+//! ```
+
+use super::CommandLine;
+use crate::config;
+use crate::error::{PortsError, Result};
+use crate::models::{AppType, Confidence, Detection, ProcessInfo};
+use regex::Regex;
+use serde::Deserialize;
+use std::cmp::Reverse;
+use std::sync::OnceLock;
+
+/// Built-in detection rules.
+const DEFAULT_RULES: &str = include_str!("default_rules.toml");
+
+/// Name of the user rules file in the configuration directory.
+pub const RULES_FILE_NAME: &str = "rules.toml";
+
+/// Contents of a rules file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RulesFile {
+    /// Names of rules to remove
+    #[serde(default)]
+    disable: Vec<String>,
+    /// Rules to add or override
+    #[serde(default, rename = "rule")]
+    rules: Vec<RuleSpec>,
+}
+
+/// A rule as written in a rules file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleSpec {
+    name: String,
+    app_type: String,
+    framework: Option<String>,
+    #[serde(default = "default_confidence")]
+    confidence: Confidence,
+    #[serde(default)]
+    process_name: Vec<String>,
+    #[serde(default)]
+    program: Vec<String>,
+    #[serde(default)]
+    first_arg: Vec<String>,
+    #[serde(default)]
+    arg: Vec<String>,
+    #[serde(default)]
+    executable: Vec<String>,
+    #[serde(default)]
+    executable_word: Vec<String>,
+    #[serde(default)]
+    command: Vec<String>,
+    #[serde(default)]
+    command_word: Vec<String>,
+    command_regex: Option<String>,
+}
+
+/// Confidence of rules that do not specify one.
+///
+/// User rules default to the highest confidence so that they take
+/// precedence over built-in rules, which all specify their confidence.
+fn default_confidence() -> Confidence {
+    Confidence::High
+}
+
+/// A detection rule.
+#[derive(Debug, Clone)]
+pub struct Rule {
+    /// Unique rule name, used to override or disable the rule
+    pub name: String,
+    /// Application type assigned when the rule matches
+    pub app_type: AppType,
+    /// Framework identified by the rule, if any
+    pub framework: Option<String>,
+    /// How certain a match of this rule is
+    pub confidence: Confidence,
+    process_name: Vec<Glob>,
+    program: Vec<Glob>,
+    first_arg: Vec<Glob>,
+    arg: Vec<Glob>,
+    executable: Vec<Glob>,
+    executable_word: Option<WordSet>,
+    command: Vec<Glob>,
+    command_word: Option<WordSet>,
+    command_regex: Option<Regex>,
+}
+
+impl Rule {
+    /// Compiles a rule from its file representation.
+    fn from_spec(spec: RuleSpec) -> std::result::Result<Self, String> {
+        let globs = |patterns: Vec<String>| patterns.iter().map(|p| Glob::new(p)).collect();
+        let command_regex = spec
+            .command_regex
+            .map(|pattern| {
+                Regex::new(&format!("(?i){}", pattern))
+                    .map_err(|e| format!("rule '{}': invalid command_regex: {}", spec.name, e))
+            })
+            .transpose()?;
+
+        let rule = Self {
+            app_type: AppType::from_name(&spec.app_type),
+            framework: spec.framework,
+            confidence: spec.confidence,
+            process_name: globs(spec.process_name),
+            program: globs(spec.program),
+            first_arg: globs(spec.first_arg),
+            arg: globs(spec.arg),
+            executable: globs(spec.executable),
+            executable_word: WordSet::new(spec.executable_word),
+            command: globs(spec.command),
+            command_word: WordSet::new(spec.command_word),
+            command_regex,
+            name: spec.name,
+        };
+
+        if rule.conditions().is_empty() {
+            return Err(format!("rule '{}' has no conditions", rule.name));
+        }
+        Ok(rule)
+    }
+
+    /// Returns the conditions of this rule as `field = pattern|pattern`.
+    pub fn conditions(&self) -> Vec<String> {
+        let globs = [
+            ("process_name", &self.process_name),
+            ("program", &self.program),
+            ("first_arg", &self.first_arg),
+            ("arg", &self.arg),
+            ("executable", &self.executable),
+            ("command", &self.command),
+        ];
+        let words = [
+            ("executable_word", &self.executable_word),
+            ("command_word", &self.command_word),
+        ];
+
+        let mut conditions: Vec<String> = globs
+            .iter()
+            .filter(|(_, globs)| !globs.is_empty())
+            .map(|(field, globs)| {
+                let patterns: Vec<&str> = globs.iter().map(|g| g.0.as_str()).collect();
+                format!("{} = {}", field, patterns.join("|"))
+            })
+            .collect();
+        conditions.extend(words.iter().filter_map(|(field, words)| {
+            words
+                .as_ref()
+                .map(|words| format!("{} = {}", field, words.words.join("|")))
+        }));
+        if let Some(regex) = &self.command_regex {
+            let pattern = regex.as_str().trim_start_matches("(?i)");
+            conditions.push(format!("command_regex = {}", pattern));
+        }
+        conditions
+    }
+
+    /// Returns how specific a match of this rule is: its confidence, then
+    /// the number of conditions it checks.
+    fn specificity(&self) -> (Confidence, usize) {
+        (self.confidence, self.conditions().len())
+    }
+
+    /// Returns true if every condition of the rule matches.
+    fn matches(&self, command: &CommandLine) -> bool {
+        let any = |globs: &[Glob], text: &str| globs.iter().any(|glob| glob.matches(text));
+
+        (self.process_name.is_empty() || any(&self.process_name, &command.name))
+            && (self.program.is_empty() || any(&self.program, &command.program))
+            && (self.first_arg.is_empty()
+                || command
+                    .first_arg()
+                    .is_some_and(|arg| any(&self.first_arg, arg)))
+            && (self.arg.is_empty() || command.has_arg(|arg| any(&self.arg, arg)))
+            && (self.executable.is_empty()
+                || command
+                    .executables
+                    .iter()
+                    .any(|path| any(&self.executable, path)))
+            && self
+                .executable_word
+                .as_ref()
+                .is_none_or(|words| command.executables.iter().any(|path| words.matches(path)))
+            && (self.command.is_empty() || any(&self.command, &command.line))
+            && self
+                .command_word
+                .as_ref()
+                .is_none_or(|words| words.matches(&command.line))
+            && self
+                .command_regex
+                .as_ref()
+                .is_none_or(|regex| regex.is_match(&command.line))
+    }
+}
+
+/// A rule that matched a process, as reported by `--explain`.
+#[derive(Debug, Clone)]
+pub struct RuleMatch {
+    /// Name of the rule
+    pub rule: String,
+    /// Application type the rule assigns
+    pub app_type: AppType,
+    /// Framework the rule identifies
+    pub framework: Option<String>,
+    /// Confidence of the rule
+    pub confidence: Confidence,
+    /// The rule's conditions, all of which matched
+    pub conditions: Vec<String>,
+}
+
+/// An ordered table of detection rules.
+#[derive(Debug, Clone)]
+pub struct RuleSet {
+    rules: Vec<Rule>,
+}
+
+impl RuleSet {
+    /// Returns the built-in rules.
+    pub fn builtin() -> &'static RuleSet {
+        static BUILTIN: OnceLock<RuleSet> = OnceLock::new();
+        BUILTIN.get_or_init(|| {
+            let file = parse_rules_file(DEFAULT_RULES).expect("built-in rules are valid");
+            let mut rule_set = RuleSet { rules: Vec::new() };
+            rule_set.merge(file).expect("built-in rules are valid");
+            rule_set
+        })
+    }
+
+    /// Returns the built-in rules combined with the user's rules file.
+    pub fn load() -> Result<RuleSet> {
+        let mut rule_set = Self::builtin().clone();
+
+        if let Some((path, content)) = config::read_config_file(RULES_FILE_NAME)? {
+            parse_rules_file(&content)
+                .and_then(|file| rule_set.merge(file))
+                .map_err(|e| PortsError::config_error(format!("{}: {}", path.display(), e)))?;
+        }
+
+        Ok(rule_set)
+    }
+
+    /// Detects the application type and framework of a process.
+    ///
+    /// When several rules match, the most specific one wins (see
+    /// [`RuleSet::ranked_matches`]). The framework comes from the most specific
+    /// matching rule that names one for the detected application type.
+    /// Returns [`Detection::unknown`] if no rule matches.
+    pub fn detect(&self, process: &ProcessInfo) -> Detection {
+        let matches = self.ranked_matches(&CommandLine::new(process));
+        let Some(rule) = matches.first() else {
+            return Detection::unknown();
+        };
+
+        let framework = matches
+            .iter()
+            .filter(|r| r.app_type == rule.app_type)
+            .find_map(|r| r.framework.clone());
+
+        Detection {
+            app_type: rule.app_type.clone(),
+            framework,
+            confidence: rule.confidence,
+            matched_rule: Some(rule.name.clone()),
+            manifest: None,
+        }
+    }
+
+    /// Returns true if a rule detects `app_type`.
+    pub fn detects(&self, app_type: &AppType) -> bool {
+        self.rules.iter().any(|rule| &rule.app_type == app_type)
+    }
+
+    /// Returns every rule matching a process, the winning rule first.
+    pub fn explain(&self, process: &ProcessInfo) -> Vec<RuleMatch> {
+        self.ranked_matches(&CommandLine::new(process))
+            .into_iter()
+            .map(|rule| RuleMatch {
+                rule: rule.name.clone(),
+                app_type: rule.app_type.clone(),
+                framework: rule.framework.clone(),
+                confidence: rule.confidence,
+                conditions: rule.conditions(),
+            })
+            .collect()
+    }
+
+    /// Returns the rules matching the process command line, most specific
+    /// first.
+    ///
+    /// Rules are ranked by confidence, then by the number of conditions;
+    /// among equally specific rules the one listed first wins.
+    fn ranked_matches(&self, command: &CommandLine) -> Vec<&Rule> {
+        let mut matches: Vec<&Rule> = self
+            .rules
+            .iter()
+            .filter(|rule| rule.matches(command))
+            .collect();
+        // Stable sort keeps the table order among equally specific rules
+        matches.sort_by_key(|rule| Reverse(rule.specificity()));
+        matches
+    }
+
+    /// Applies a rules file: disables rules, replaces rules with the same
+    /// name in place and puts new rules in front of the existing ones.
+    fn merge(&mut self, file: RulesFile) -> std::result::Result<(), String> {
+        for name in &file.disable {
+            let before = self.rules.len();
+            self.rules.retain(|rule| &rule.name != name);
+            if self.rules.len() == before {
+                return Err(format!("cannot disable unknown rule '{}'", name));
+            }
+        }
+
+        let mut added = Vec::new();
+        for spec in file.rules {
+            let rule = Rule::from_spec(spec)?;
+            if added.iter().any(|r: &Rule| r.name == rule.name) {
+                return Err(format!("duplicate rule '{}'", rule.name));
+            }
+            match self.rules.iter_mut().find(|r| r.name == rule.name) {
+                Some(existing) => *existing = rule,
+                None => added.push(rule),
+            }
+        }
+
+        added.append(&mut self.rules);
+        self.rules = added;
+        Ok(())
+    }
+}
+
+/// Parses a rules file.
+fn parse_rules_file(content: &str) -> std::result::Result<RulesFile, String> {
+    toml::from_str(content).map_err(|e| e.message().to_string())
+}
+
+/// A case-insensitive glob pattern where `*` matches any text.
+#[derive(Debug, Clone)]
+pub struct Glob(String);
+
+impl Glob {
+    /// Creates a glob from a pattern.
+    pub fn new(pattern: &str) -> Self {
+        Self(pattern.to_lowercase())
+    }
+
+    /// Matches `text`, which must already be lowercase.
+    pub fn matches(&self, text: &str) -> bool {
+        let mut parts = self.0.split('*');
+        let first = parts.next().unwrap_or_default();
+        let Some(mut rest) = text.strip_prefix(first) else {
+            return false;
+        };
+
+        let mut parts: Vec<&str> = parts.collect();
+        let Some(last) = parts.pop() else {
+            // No wildcard: the pattern must match exactly
+            return rest.is_empty();
+        };
+
+        for part in parts {
+            match rest.find(part) {
+                Some(index) => rest = &rest[index + part.len()..],
+                None => return false,
+            }
+        }
+        rest.ends_with(last)
+    }
+}
+
+/// A set of whole words matched case-insensitively.
+///
+/// Words only match between non-word characters (or the ends of the text),
+/// which unlike `\b` also works for words such as `c++`. `gin` matches
+/// `/srv/gin-api/server` but not `nginx` or `login`.
+#[derive(Debug, Clone)]
+struct WordSet {
+    words: Vec<String>,
+    regex: Regex,
+}
+
+impl WordSet {
+    /// Returns `None` for an empty word list.
+    fn new(words: Vec<String>) -> Option<Self> {
+        if words.is_empty() {
+            return None;
+        }
+
+        let alternatives: Vec<String> = words.iter().map(|w| regex::escape(w)).collect();
+        let regex = Regex::new(&format!(
+            r"(?i)(?:^|\W)(?:{})(?:\W|$)",
+            alternatives.join("|")
+        ))
+        .expect("escaped words form a valid regex");
+        Some(Self { words, regex })
+    }
+
+    fn matches(&self, text: &str) -> bool {
+        self.regex.is_match(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command_line(name: &str, argv: &[&str]) -> CommandLine {
+        let mut process = ProcessInfo::new(1, name);
+        process.argv = argv.iter().map(|a| a.to_string()).collect();
+        CommandLine::new(&process)
+    }
+
+    fn rule_set(content: &str) -> std::result::Result<RuleSet, String> {
+        let mut rule_set = RuleSet::builtin().clone();
+        rule_set.merge(parse_rules_file(content)?)?;
+        Ok(rule_set)
+    }
+
+    fn detect(rule_set: &RuleSet, name: &str, argv: &[&str]) -> Option<(String, AppType)> {
+        rule_set
+            .ranked_matches(&command_line(name, argv))
+            .first()
+            .map(|rule| (rule.name.clone(), rule.app_type.clone()))
+    }
+
+    #[test]
+    fn test_glob() {
+        assert!(Glob::new("node").matches("node"));
+        assert!(!Glob::new("node").matches("nodemon"));
+        assert!(Glob::new("python*").matches("python3.12"));
+        assert!(Glob::new("*.DLL").matches("/app/api.dll"));
+        assert!(Glob::new("*/target/*/").matches("/src/target/debug/"));
+        assert!(Glob::new("*vite*").matches("vite"));
+        assert!(!Glob::new("a*b*c").matches("acb"));
+        assert!(Glob::new("*").matches(""));
+    }
+
+    #[test]
+    fn test_builtin_rules_are_valid() {
+        let rules = &RuleSet::builtin().rules;
+        assert!(!rules.is_empty());
+
+        let mut names: Vec<&str> = rules.iter().map(|r| r.name.as_str()).collect();
+        names.sort_unstable();
+        names.dedup();
+        assert_eq!(names.len(), rules.len(), "rule names must be unique");
+    }
+
+    #[test]
+    fn test_all_conditions_must_match() {
+        let rules = RuleSet::builtin();
+
+        assert_eq!(
+            detect(rules, "cargo", &["cargo", "run"]).map(|(name, _)| name),
+            Some("cargo-run".to_string())
+        );
+        assert_eq!(detect(rules, "cargo", &["cargo", "test"]), None);
+    }
+
+    #[test]
+    fn test_word_set() {
+        let words = WordSet::new(vec!["gin".to_string(), "c++".to_string()]).unwrap();
+
+        assert!(words.matches("/srv/gin-api/server"));
+        assert!(words.matches("/opt/GIN/bin"));
+        assert!(words.matches("/usr/bin/c++ main.cc"));
+        assert!(!words.matches("/usr/sbin/nginx"));
+        assert!(!words.matches("/bin/login"));
+        assert!(WordSet::new(Vec::new()).is_none());
+    }
+
+    #[test]
+    fn test_go_frameworks_false_positives() {
+        let rules = RuleSet::builtin();
+        let app_type = |name, argv| detect(rules, name, argv).map(|(_, t)| t);
+
+        assert_eq!(
+            app_type("nginx", &["nginx: master process /usr/sbin/nginx"]),
+            Some(AppType::Nginx)
+        );
+        assert_eq!(app_type("login", &["/bin/login", "-p"]), None);
+        assert_eq!(app_type("sh", &["/bin/sh", "-c", "echo ready"]), None);
+        assert_eq!(
+            app_type("server", &["/home/me/gin-api/server"]),
+            Some(AppType::Go)
+        );
+    }
+
+    #[test]
+    fn test_most_specific_rule_wins() {
+        let rules = RuleSet::builtin();
+
+        // node-tooling (medium) and python-program (high) both match
+        assert_eq!(
+            detect(rules, "python3", &["python3", "-m", "http.server", "next"]),
+            Some(("python-program".to_string(), AppType::Python))
+        );
+        // A Go binary built by `go run` under a directory named like a framework
+        assert_eq!(
+            detect(rules, "main", &["/tmp/go-build123/b001/exe/main"]).map(|(name, _)| name),
+            Some("go-build-cache".to_string())
+        );
+        // nginx (high) beats the low-confidence command rule listed later
+        assert_eq!(
+            detect(rules, "nginx", &["nginx"]).map(|(name, _)| name),
+            Some("nginx".to_string())
+        );
+    }
+
+    #[test]
+    fn test_detect_and_explain() {
+        let rules = RuleSet::builtin();
+        let mut process = ProcessInfo::new(1, "node");
+        process.argv = vec!["node".to_string(), "vite".to_string()];
+
+        let detection = rules.detect(&process);
+        assert_eq!(detection.app_type, AppType::NodeJs);
+        assert_eq!(detection.confidence, Confidence::High);
+        assert_eq!(detection.matched_rule.as_deref(), Some("node-program"));
+        assert_eq!(detection.framework.as_deref(), Some("Vite"));
+
+        let matches = rules.explain(&process);
+        let names: Vec<&str> = matches.iter().map(|m| m.rule.as_str()).collect();
+        assert_eq!(
+            names,
+            ["node-program", "node-process", "node-tooling", "vite"]
+        );
+        assert_eq!(
+            matches[0].conditions,
+            ["program = node|nodejs|npm|npx|yarn|pnpm"]
+        );
+
+        let unknown = rules.detect(&ProcessInfo::new(2, "sshd"));
+        assert_eq!(unknown.app_type, AppType::Unknown);
+        assert_eq!(unknown.matched_rule, None);
+        assert!(rules.explain(&ProcessInfo::new(2, "sshd")).is_empty());
+    }
+
+    #[test]
+    fn test_detect_framework() {
+        let rules = RuleSet::builtin();
+        let detect = |name: &str, argv: &[&str]| {
+            let mut process = ProcessInfo::new(1, name);
+            process.argv = argv.iter().map(|a| a.to_string()).collect();
+            let detection = rules.detect(&process);
+            (detection.app_type, detection.framework)
+        };
+
+        assert_eq!(
+            detect("node", &["node", "/app/node_modules/.bin/next", "dev"]),
+            (AppType::NodeJs, Some("Next.js".to_string()))
+        );
+        assert_eq!(
+            detect("next-server (v1", &["next-server (v14.1.0)"]),
+            (AppType::NodeJs, Some("Next.js".to_string()))
+        );
+        assert_eq!(
+            detect("python3", &["python3", "manage.py", "runserver"]),
+            (AppType::Python, Some("Django".to_string()))
+        );
+        assert_eq!(
+            detect("ruby", &["ruby", "bin/rails", "server"]),
+            (AppType::Ruby, Some("Rails".to_string()))
+        );
+        assert_eq!(
+            detect("node", &["node", "server.js"]),
+            (AppType::NodeJs, None)
+        );
+    }
+
+    #[test]
+    fn test_framework_requires_same_app_type() {
+        let rules = rule_set(
+            r#"
+            [[rule]]
+            name = "remix"
+            app_type = "Ruby"
+            framework = "Remix"
+            confidence = "low"
+            arg = ["remix"]
+            "#,
+        )
+        .unwrap();
+        let mut process = ProcessInfo::new(1, "node");
+        process.argv = vec!["node".to_string(), "remix".to_string()];
+
+        let detection = rules.detect(&process);
+        assert_eq!(detection.app_type, AppType::NodeJs);
+        assert_eq!(detection.framework, None);
+    }
+
+    #[test]
+    fn test_user_rule_added_before_builtin_rules() {
+        let rules = rule_set(
+            r#"
+            [[rule]]
+            name = "gleam"
+            app_type = "Gleam"
+            program = ["beam.smp"]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(rules.rules[0].name, "gleam");
+        assert_eq!(
+            detect(
+                &rules,
+                "beam.smp",
+                &["/usr/lib/erlang/erts/bin/beam.smp", "--", "-root"]
+            ),
+            Some(("gleam".to_string(), AppType::Custom("Gleam".to_string())))
+        );
+        assert!(rules.detects(&AppType::Custom("Gleam".to_string())));
+        assert!(!RuleSet::builtin().detects(&AppType::Custom("Gleam".to_string())));
+    }
+
+    #[test]
+    fn test_user_rule_overrides_builtin_rule() {
+        let rules = rule_set(
+            r#"
+            [[rule]]
+            name = "go-frameworks"
+            app_type = "Go"
+            command_regex = '\bgin\b'
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(rules.rules.len(), RuleSet::builtin().rules.len());
+        assert_eq!(detect(&rules, "login", &["/bin/login"]), None);
+        assert_eq!(
+            detect(&rules, "api", &["./api", "--mode", "gin"]).map(|(_, t)| t),
+            Some(AppType::Go)
+        );
+    }
+
+    #[test]
+    fn test_disable_builtin_rule() {
+        let rules = rule_set(r#"disable = ["node-process", "node-program"]"#).unwrap();
+
+        assert!(rules.rules.iter().all(|r| r.name != "node-process"));
+        assert_eq!(detect(&rules, "node", &["node", "server.js"]), None);
+    }
+
+    #[test]
+    fn test_invalid_rules_files() {
+        assert!(rule_set(r#"disable = ["no-such-rule"]"#).is_err());
+        assert!(rule_set("[[rule]]\nname = \"x\"\napp_type = \"X\"\n").is_err());
+        assert!(
+            rule_set("[[rule]]\nname = \"x\"\napp_type = \"X\"\nprogramm = [\"x\"]\n").is_err()
+        );
+        assert!(
+            rule_set("[[rule]]\nname = \"x\"\napp_type = \"X\"\ncommand_regex = \"(\"\n").is_err()
+        );
+        assert!(rule_set(
+            "[[rule]]\nname = \"x\"\napp_type = \"X\"\nprogram = [\"a\"]\n\
+             [[rule]]\nname = \"x\"\napp_type = \"X\"\nprogram = [\"b\"]\n"
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,142 @@
+//! Runtime version detection.
+//!
+//! Version managers and package managers install each runtime version in
+//! its own directory, so the version can be read from the executable path
+//! without running the executable:
+//!
+//! - nvm: `~/.nvm/versions/node/v20.11.0/bin/node`
+//! - pyenv: `~/.pyenv/versions/3.12.1/bin/python3.12` (pyenv shims exec
+//!   this path, so it is the executable of the running process)
+//! - Homebrew: `/opt/homebrew/Cellar/node/21.6.1/bin/node`
+
+use crate::models::ProcessInfo;
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// Path patterns capturing the runtime version, checked in order.
+const VERSION_PATTERNS: &[&str] = &[
+    // nvm
+    r"/\.nvm/versions/[^/]+/v?(\d+(?:\.\d+)*)/",
+    // fnm
+    r"/node-versions/v?(\d+(?:\.\d+)*)/",
+    // Volta
+    r"/\.volta/tools/image/[^/]+/v?(\d+(?:\.\d+)*)/",
+    // pyenv, rbenv, nodenv, goenv
+    r"/\.[a-z]+env/versions/v?(\d+(?:\.\d+)*)[^/]*/",
+    // asdf and mise
+    r"/(?:\.asdf/installs|mise/installs)/[^/]+/v?(\d+(?:\.\d+)*)[^/]*/",
+    // Homebrew
+    r"/Cellar/[^/]+/(\d+(?:\.\d+)*)[^/]*/",
+    // JDK installations, e.g. /usr/lib/jvm/java-17-openjdk-amd64
+    r"/(?:jvm|JavaVirtualMachines)/[a-z]+(?:-[a-z]+)*-?(\d+(?:\.\d+)*)",
+    // Versioned interpreters, e.g. /usr/bin/python3.12
+    r"/(?:python|ruby|php)(\d+\.\d+)$",
+];
+
+/// Returns the runtime version of a process if its executable path
+/// reveals one.
+///
+/// The executable is checked before `argv[0]`, since `argv[0]` is often a
+/// shim or a bare program name.
+pub fn runtime_version(process: &ProcessInfo) -> Option<String> {
+    static PATTERNS: OnceLock<Vec<Regex>> = OnceLock::new();
+    let patterns = PATTERNS.get_or_init(|| {
+        VERSION_PATTERNS
+            .iter()
+            .map(|p| Regex::new(p).expect("version patterns are valid"))
+            .collect()
+    });
+
+    let exe = process.exe.as_ref().map(|exe| exe.to_string_lossy());
+    let paths = exe.iter().map(|exe| exe.as_ref()).chain(
+        process
+            .argv
+            .first()
+            .map(String::as_str)
+            .filter(|a| a.contains('/')),
+    );
+
+    for path in paths {
+        for pattern in patterns {
+            if let Some(captures) = pattern.captures(path) {
+                return Some(captures[1].to_string());
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn version_of(exe: Option<&str>, argv0: &str) -> Option<String> {
+        let mut process = ProcessInfo::new(1, "test");
+        process.exe = exe.map(PathBuf::from);
+        process.argv = vec![argv0.to_string()];
+        runtime_version(&process)
+    }
+
+    #[test]
+    fn test_version_manager_paths() {
+        let cases = [
+            ("/home/me/.nvm/versions/node/v20.11.0/bin/node", "20.11.0"),
+            ("/home/me/.pyenv/versions/3.12.1/bin/python3.12", "3.12.1"),
+            ("/home/me/.rbenv/versions/3.3.0/bin/ruby", "3.3.0"),
+            ("/home/me/.asdf/installs/nodejs/18.19.0/bin/node", "18.19.0"),
+            (
+                "/home/me/.volta/tools/image/node/20.10.0/bin/node",
+                "20.10.0",
+            ),
+            (
+                "/home/me/.local/share/fnm/node-versions/v21.5.0/installation/bin/node",
+                "21.5.0",
+            ),
+            ("/opt/homebrew/Cellar/node/21.6.1/bin/node", "21.6.1"),
+            (
+                "/opt/homebrew/Cellar/python@3.12/3.12.1_1/bin/python3.12",
+                "3.12.1",
+            ),
+            ("/usr/lib/jvm/java-17-openjdk-amd64/bin/java", "17"),
+            (
+                "/Library/Java/JavaVirtualMachines/temurin-21.jdk/Contents/Home/bin/java",
+                "21",
+            ),
+            ("/usr/bin/python3.11", "3.11"),
+        ];
+
+        for (path, version) in cases {
+            assert_eq!(
+                version_of(Some(path), "x").as_deref(),
+                Some(version),
+                "{}",
+                path
+            );
+        }
+    }
+
+    #[test]
+    fn test_executable_preferred_over_argv() {
+        // A pyenv shim resolves to the interpreter of the selected version
+        assert_eq!(
+            version_of(
+                Some("/home/me/.pyenv/versions/3.11.7/bin/python3.11"),
+                "/home/me/.pyenv/shims/python"
+            )
+            .as_deref(),
+            Some("3.11.7")
+        );
+        assert_eq!(
+            version_of(None, "/home/me/.nvm/versions/node/v18.0.0/bin/node").as_deref(),
+            Some("18.0.0")
+        );
+    }
+
+    #[test]
+    fn test_no_version() {
+        assert_eq!(version_of(Some("/usr/bin/node"), "node"), None);
+        assert_eq!(version_of(None, "python3.12"), None);
+        assert_eq!(version_of(None, "/home/me/.pyenv/shims/python"), None);
+    }
+}
@@ -0,0 +1,518 @@
+//! Network exposure audit.
+//!
+//! This module classifies listening sockets by how far they are reachable
+//! (loopback only, a specific LAN address, or every interface) and flags
+//! services that should never be reachable from other machines, such as
+//! databases and debugger ports.
+
+use crate::models::{AppCategory, AppType, PortEntry};
+use serde::Serialize;
+use std::net::IpAddr;
+
+/// Ports that must never listen on a non-loopback address by default.
+pub const DEFAULT_NEVER_PUBLIC_PORTS: &[u16] = &[
+    2375,  // Docker daemon API (unauthenticated)
+    3306,  // MySQL
+    5005,  // Java debugger (JDWP)
+    5432,  // PostgreSQL
+    5678,  // Python debugger (debugpy)
+    6379,  // Redis
+    9200,  // Elasticsearch
+    9229,  // Node.js inspector
+    11211, // Memcached
+    27017, // MongoDB
+];
+
+/// How far a listening socket can be reached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Exposure {
+    /// Bound to a loopback address; reachable from this machine only
+    LoopbackOnly,
+    /// Bound to a specific non-loopback address (e.g. the Wi-Fi interface)
+    LanExposed,
+    /// Bound to the unspecified address (`0.0.0.0` or `::`)
+    AllInterfaces,
+    /// The bind address could not be determined
+    Unknown,
+}
+
+impl Exposure {
+    /// Classifies a bind address.
+    pub fn of(address: Option<IpAddr>) -> Self {
+        let Some(address) = address else {
+            return Exposure::Unknown;
+        };
+
+        // IPv4-mapped IPv6 addresses (::ffff:127.0.0.1) behave like IPv4
+        let address = match address {
+            IpAddr::V6(v6) => v6.to_ipv4_mapped().map_or(address, IpAddr::V4),
+            IpAddr::V4(_) => address,
+        };
+
+        if address.is_loopback() {
+            Exposure::LoopbackOnly
+        } else if address.is_unspecified() {
+            Exposure::AllInterfaces
+        } else {
+            Exposure::LanExposed
+        }
+    }
+
+    /// Returns the display name for this exposure level.
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Exposure::LoopbackOnly => "loopback",
+            Exposure::LanExposed => "LAN",
+            Exposure::AllInterfaces => "all",
+            Exposure::Unknown => "unknown",
+        }
+    }
+}
+
+/// Severity of an audit finding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum Severity {
+    /// Reachable from this machine only
+    Ok,
+    /// Reachable from other machines; review whether this is intended
+    Warning,
+    /// A service that must never be reachable from other machines
+    Violation,
+}
+
+impl Severity {
+    /// Returns the display name for this severity.
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Severity::Ok => "OK",
+            Severity::Warning => "WARN",
+            Severity::Violation => "FAIL",
+        }
+    }
+}
+
+/// Audit settings.
+#[derive(Debug, Clone)]
+pub struct AuditPolicy {
+    /// Ports that must only listen on loopback addresses
+    pub never_public: Vec<u16>,
+}
+
+impl Default for AuditPolicy {
+    fn default() -> Self {
+        Self {
+            never_public: DEFAULT_NEVER_PUBLIC_PORTS.to_vec(),
+        }
+    }
+}
+
+/// The audit result for a single listening socket.
+#[derive(Debug, Clone, Serialize)]
+pub struct Finding {
+    /// The audited port entry
+    #[serde(flatten)]
+    pub entry: PortEntry,
+    /// How far the socket can be reached
+    pub exposure: Exposure,
+    /// How serious the exposure is
+    pub severity: Severity,
+    /// Why the finding is a warning or violation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// The result of auditing all listening sockets.
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditReport {
+    /// One finding per port entry, most severe first
+    pub findings: Vec<Finding>,
+    /// Number of findings with [`Severity::Violation`]
+    pub violation_count: usize,
+    /// Number of findings with [`Severity::Warning`]
+    pub warning_count: usize,
+}
+
+impl AuditReport {
+    /// Returns true if any violation was found.
+    pub fn has_violations(&self) -> bool {
+        self.violation_count > 0
+    }
+}
+
+/// Audits port entries against a policy.
+pub fn audit_entries(entries: Vec<PortEntry>, policy: &AuditPolicy) -> AuditReport {
+    let mut findings: Vec<Finding> = entries
+        .into_iter()
+        .map(|entry| audit_entry(entry, policy))
+        .collect();
+
+    // Stable sort keeps the port order within each severity
+    findings.sort_by_key(|f| std::cmp::Reverse(f.severity));
+
+    let count = |severity| findings.iter().filter(|f| f.severity == severity).count();
+    let violation_count = count(Severity::Violation);
+    let warning_count = count(Severity::Warning);
+
+    AuditReport {
+        findings,
+        violation_count,
+        warning_count,
+    }
+}
+
+/// Classifies a single port entry.
+fn audit_entry(entry: PortEntry, policy: &AuditPolicy) -> Finding {
+    let exposure = Exposure::of(entry.bind_address);
+
+    let (severity, reason) = if exposure == Exposure::LoopbackOnly {
+        (Severity::Ok, None)
+    } else if policy.never_public.contains(&entry.port) {
+        (
+            Severity::Violation,
+            Some(format!(
+                "ポート{}は外部公開禁止リストに含まれています",
+                entry.port
+            )),
+        )
+    } else if has_debugger_enabled(&entry) {
+        (
+            Severity::Violation,
+            Some("デバッガが外部からアクセス可能です".to_string()),
+        )
+    } else if exposure == Exposure::Unknown {
+        (
+            Severity::Warning,
+            Some("バインドアドレスを取得できませんでした".to_string()),
+        )
+    } else if entry
+        .app_type
+        .as_ref()
+        .is_some_and(|t| t.category() == AppCategory::Runtime)
+    {
+        (
+            Severity::Warning,
+            Some("開発サーバーが他のマシンからアクセス可能です".to_string()),
+        )
+    } else {
+        (
+            Severity::Warning,
+            Some("他のマシンからアクセス可能です".to_string()),
+        )
+    };
+
+    Finding {
+        entry,
+        exposure,
+        severity,
+        reason,
+    }
+}
+
+/// Returns true if the entry exposes the debugger of its process.
+///
+/// A debugger port grants arbitrary code execution. The debugger's own
+/// socket is always flagged, and so is every socket of a process whose
+/// debugger listens on a non-loopback address. A debugger on loopback
+/// (the default of `node --inspect`) does not make the other ports of the
+/// process a violation.
+fn has_debugger_enabled(entry: &PortEntry) -> bool {
+    let Some(debugger) = debugger_endpoint(entry) else {
+        return false;
+    };
+
+    debugger.port == Some(entry.port) || !debugger.is_loopback()
+}
+
+/// Where the debugger of a process listens, as given on its command line.
+#[derive(Debug, PartialEq, Eq)]
+struct DebuggerEndpoint {
+    /// The host the debugger binds to (`*` for every interface)
+    host: String,
+    /// The debugger port, if given
+    port: Option<u16>,
+}
+
+impl DebuggerEndpoint {
+    /// Parses `[host:]port`, using `default_host` when no host is given.
+    fn parse(spec: &str, default_host: &str) -> Self {
+        let (host, port) = match spec.rsplit_once(':') {
+            Some((host, port)) => (host, port),
+            None if spec.parse::<u16>().is_ok() => (default_host, spec),
+            None => (spec, ""),
+        };
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+
+        Self {
+            host: if host.is_empty() { default_host } else { host }.to_string(),
+            port: port.parse().ok(),
+        }
+    }
+
+    /// Returns true if the debugger is reachable from this machine only.
+    fn is_loopback(&self) -> bool {
+        self.host == "localhost" || Exposure::of(self.host.parse().ok()) == Exposure::LoopbackOnly
+    }
+}
+
+/// Reads the debugger endpoint from the arguments of the process.
+///
+/// Understands `--inspect[-brk|-wait][=[host:]port]` (Node.js, Deno, Bun),
+/// `-agentlib:jdwp=...,address=[host:]port` (Java) and
+/// `debugpy --listen [host:]port` (Python). Debuggers default to loopback,
+/// except JDWP before Java 9, which is not distinguished here.
+fn debugger_endpoint(entry: &PortEntry) -> Option<DebuggerEndpoint> {
+    const LOOPBACK: &str = "127.0.0.1";
+    let args = entry.process.argv.get(1..).unwrap_or_default();
+
+    match entry.app_type {
+        Some(AppType::NodeJs | AppType::Deno | AppType::Bun) => args.iter().find_map(|a| {
+            let (flag, spec) = a.split_once('=').unwrap_or((a, ""));
+            matches!(flag, "--inspect" | "--inspect-brk" | "--inspect-wait").then(|| {
+                let endpoint = DebuggerEndpoint::parse(spec, LOOPBACK);
+                DebuggerEndpoint {
+                    port: endpoint.port.or(Some(9229)),
+                    ..endpoint
+                }
+            })
+        }),
+        Some(AppType::Java) => args.iter().find_map(|a| {
+            let options = a.strip_prefix("-agentlib:jdwp")?;
+            let address = options
+                .trim_start_matches('=')
+                .split(',')
+                .find_map(|option| option.strip_prefix("address="))
+                .unwrap_or_default();
+            Some(DebuggerEndpoint::parse(address, LOOPBACK))
+        }),
+        Some(AppType::Python) => {
+            let debugpy = args.iter().position(|a| a == "debugpy")?;
+            let rest = &args[debugpy + 1..];
+            let listen = rest.iter().enumerate().find_map(|(i, a)| match a.as_str() {
+                "--listen" => rest.get(i + 1).map(String::as_str),
+                _ => a.strip_prefix("--listen="),
+            });
+            Some(DebuggerEndpoint::parse(
+                listen.unwrap_or_default(),
+                LOOPBACK,
+            ))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ProcessInfo, Protocol};
+
+    fn make_entry(port: u16, address: &str, app_type: AppType) -> PortEntry {
+        let process = ProcessInfo::new(1234, "test");
+        PortEntry::new(port, Protocol::Tcp, process)
+            .with_bind_address(address.parse().unwrap())
+            .with_app_type(app_type)
+    }
+
+    #[test]
+    fn test_exposure_of() {
+        let exposure = |address: &str| Exposure::of(address.parse().ok());
+
+        assert_eq!(exposure("127.0.0.1"), Exposure::LoopbackOnly);
+        assert_eq!(exposure("127.0.0.53"), Exposure::LoopbackOnly);
+        assert_eq!(exposure("::1"), Exposure::LoopbackOnly);
+        assert_eq!(exposure("::ffff:127.0.0.1"), Exposure::LoopbackOnly);
+        assert_eq!(exposure("0.0.0.0"), Exposure::AllInterfaces);
+        assert_eq!(exposure("::"), Exposure::AllInterfaces);
+        assert_eq!(exposure("192.168.1.5"), Exposure::LanExposed);
+        assert_eq!(exposure("fe80::1"), Exposure::LanExposed);
+        assert_eq!(Exposure::of(None), Exposure::Unknown);
+    }
+
+    #[test]
+    fn test_loopback_is_ok() {
+        let report = audit_entries(
+            vec![make_entry(5432, "127.0.0.1", AppType::Unknown)],
+            &AuditPolicy::default(),
+        );
+
+        assert_eq!(report.findings[0].severity, Severity::Ok);
+        assert!(report.findings[0].reason.is_none());
+        assert!(!report.has_violations());
+    }
+
+    #[test]
+    fn test_never_public_port_is_violation() {
+        let report = audit_entries(
+            vec![
+                make_entry(3000, "0.0.0.0", AppType::NodeJs),
+                make_entry(6379, "0.0.0.0", AppType::Unknown),
+                make_entry(5432, "192.168.1.5", AppType::Unknown),
+            ],
+            &AuditPolicy::default(),
+        );
+
+        let summary: Vec<(u16, Severity)> = report
+            .findings
+            .iter()
+            .map(|f| (f.entry.port, f.severity))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (6379, Severity::Violation),
+                (5432, Severity::Violation),
+                (3000, Severity::Warning),
+            ]
+        );
+        assert_eq!(report.violation_count, 2);
+        assert_eq!(report.warning_count, 1);
+        assert!(report.has_violations());
+    }
+
+    #[test]
+    fn test_custom_policy() {
+        let policy = AuditPolicy {
+            never_public: vec![3000],
+        };
+        let report = audit_entries(
+            vec![
+                make_entry(3000, "::", AppType::NodeJs),
+                make_entry(5432, "::", AppType::Unknown),
+            ],
+            &policy,
+        );
+
+        assert_eq!(report.findings[0].entry.port, 3000);
+        assert_eq!(report.findings[0].severity, Severity::Violation);
+        assert_eq!(report.findings[1].severity, Severity::Warning);
+    }
+
+    #[test]
+    fn test_exposed_debugger_is_violation() {
+        let mut entry = make_entry(9230, "0.0.0.0", AppType::NodeJs);
+        entry.process.argv = vec![
+            "node".to_string(),
+            "--inspect=0.0.0.0:9230".to_string(),
+            "server.js".to_string(),
+        ];
+
+        let report = audit_entries(vec![entry], &AuditPolicy::default());
+
+        assert_eq!(report.findings[0].severity, Severity::Violation);
+    }
+
+    #[test]
+    fn test_loopback_debugger_keeps_app_port_a_warning() {
+        let mut entry = make_entry(3000, "0.0.0.0", AppType::NodeJs);
+        entry.process.argv = vec![
+            "node".to_string(),
+            "--inspect".to_string(),
+            "server.js".to_string(),
+        ];
+
+        let report = audit_entries(vec![entry], &AuditPolicy::default());
+
+        assert_eq!(report.findings[0].severity, Severity::Warning);
+        assert!(!report.has_violations());
+    }
+
+    #[test]
+    fn test_debugger_endpoint() {
+        let endpoint = |app_type: AppType, args: &[&str]| {
+            let mut entry = make_entry(8080, "0.0.0.0", app_type);
+            entry.process.argv = args.iter().map(|a| a.to_string()).collect();
+            debugger_endpoint(&entry).map(|e| (e.host, e.port))
+        };
+        let at = |host: &str, port: u16| Some((host.to_string(), Some(port)));
+
+        assert_eq!(
+            endpoint(AppType::NodeJs, &["node", "--inspect"]),
+            at("127.0.0.1", 9229)
+        );
+        assert_eq!(
+            endpoint(AppType::NodeJs, &["node", "--inspect-brk=9230", "app.js"]),
+            at("127.0.0.1", 9230)
+        );
+        assert_eq!(
+            endpoint(
+                AppType::Deno,
+                &["deno", "run", "--inspect=0.0.0.0:9229", "main.ts"]
+            ),
+            at("0.0.0.0", 9229)
+        );
+        assert_eq!(
+            endpoint(AppType::NodeJs, &["node", "--inspect=[::]:9229"]),
+            at("::", 9229)
+        );
+        assert_eq!(endpoint(AppType::NodeJs, &["node", "server.js"]), None);
+        assert_eq!(
+            endpoint(
+                AppType::Java,
+                &[
+                    "java",
+                    "-agentlib:jdwp=transport=dt_socket,server=y,address=*:5005",
+                    "-jar",
+                    "app.jar"
+                ]
+            ),
+            at("*", 5005)
+        );
+        assert_eq!(
+            endpoint(
+                AppType::Java,
+                &["java", "-agentlib:jdwp=transport=dt_socket,address=5005"]
+            ),
+            at("127.0.0.1", 5005)
+        );
+        assert_eq!(
+            endpoint(
+                AppType::Python,
+                &[
+                    "python",
+                    "-m",
+                    "debugpy",
+                    "--listen",
+                    "0.0.0.0:5678",
+                    "app.py"
+                ]
+            ),
+            at("0.0.0.0", 5678)
+        );
+        assert_eq!(
+            endpoint(
+                AppType::Python,
+                &["python", "-m", "debugpy", "--listen=5678", "app.py"]
+            ),
+            at("127.0.0.1", 5678)
+        );
+    }
+
+    #[test]
+    fn test_debugger_socket_is_violation() {
+        let argv = |args: &[&str]| args.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+
+        let mut debugger = make_entry(9230, "192.168.1.5", AppType::NodeJs);
+        debugger.process.argv = argv(&["node", "--inspect=9230", "server.js"]);
+        let mut app = make_entry(3000, "0.0.0.0", AppType::Java);
+        app.process.argv = argv(&["java", "-agentlib:jdwp=transport=dt_socket,address=*:5005"]);
+
+        let report = audit_entries(vec![debugger, app], &AuditPolicy::default());
+
+        assert_eq!(report.violation_count, 2);
+    }
+
+    #[test]
+    fn test_finding_json_flattens_entry() {
+        let report = audit_entries(
+            vec![make_entry(6379, "0.0.0.0", AppType::Unknown)],
+            &AuditPolicy::default(),
+        );
+
+        let json = serde_json::to_value(&report).unwrap();
+        let finding = &json["findings"][0];
+        assert_eq!(finding["port"], 6379);
+        assert_eq!(finding["bind_address"], "0.0.0.0");
+        assert_eq!(finding["exposure"], "AllInterfaces");
+        assert_eq!(finding["severity"], "Violation");
+        assert_eq!(json["violation_count"], 1);
+    }
+}
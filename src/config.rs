@@ -0,0 +1,39 @@
+//! User configuration files.
+//!
+//! Configuration lives in `$XDG_CONFIG_HOME/ports` (usually
+//! `~/.config/ports`) on every platform, so the same dotfiles work on macOS
+//! and Linux.
+
+use crate::error::{PortsError, Result};
+use std::env;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+/// Returns the configuration directory, if a home directory is known.
+pub fn config_dir() -> Option<PathBuf> {
+    let base = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("ports"))
+}
+
+/// Reads a file from the configuration directory.
+///
+/// Returns `None` if the file does not exist.
+pub fn read_config_file(file_name: &str) -> Result<Option<(PathBuf, String)>> {
+    let Some(path) = config_dir().map(|dir| dir.join(file_name)) else {
+        return Ok(None);
+    };
+
+    match fs::read_to_string(&path) {
+        Ok(content) => Ok(Some((path, content))),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(PortsError::config_error(format!(
+            "Failed to read {}: {}",
+            path.display(),
+            e
+        ))),
+    }
+}
@@ -12,22 +12,36 @@ pub enum PortsError {
     /// This typically occurs when trying to read details of processes
     /// owned by other users without elevated privileges.
     #[error("Permission denied: {message}")]
-    PermissionDenied {
-        message: String,
-    },
+    PermissionDenied { message: String, hint: &'static str },
 
     /// The process was not found, likely because it terminated
     /// between the port scan and the detailed info retrieval.
     #[error("Process not found: PID {pid}")]
-    ProcessNotFound {
+    ProcessNotFound { pid: u32 },
+
+    /// No process is listening on the requested port.
+    #[error("No process is listening on port {port}")]
+    PortNotInUse { port: u16 },
+
+    /// The process is protected and `--force` was not given.
+    #[error("PID {pid} ({name}) is protected: {reason}")]
+    Protected {
         pid: u32,
+        name: String,
+        reason: String,
     },
 
+    /// A `--where` query could not be parsed.
+    #[error("Invalid query: {message}")]
+    InvalidQuery { message: String },
+
     /// A system-level error occurred while interacting with OS APIs.
     #[error("System error: {message}")]
-    SystemError {
-        message: String,
-    },
+    SystemError { message: String },
+
+    /// A user configuration file could not be read or is invalid.
+    #[error("Configuration error: {message}")]
+    ConfigError { message: String },
 
     /// An I/O error occurred.
     #[error("I/O error: {0}")]
@@ -37,8 +51,18 @@ pub enum PortsError {
 impl PortsError {
     /// Creates a new PermissionDenied error with the given message.
     pub fn permission_denied(message: impl Into<String>) -> Self {
+        Self::permission_denied_with_hint(
+            message,
+            "Try running with elevated privileges (sudo) to see all process details",
+        )
+    }
+
+    /// Creates a new PermissionDenied error with a hint specific to the
+    /// operation that was denied.
+    pub fn permission_denied_with_hint(message: impl Into<String>, hint: &'static str) -> Self {
         Self::PermissionDenied {
             message: message.into(),
+            hint,
         }
     }
 
@@ -47,6 +71,27 @@ impl PortsError {
         Self::ProcessNotFound { pid }
     }
 
+    /// Creates a new PortNotInUse error for the given port.
+    pub fn port_not_in_use(port: u16) -> Self {
+        Self::PortNotInUse { port }
+    }
+
+    /// Creates a new Protected error for a process.
+    pub fn protected(pid: u32, name: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self::Protected {
+            pid,
+            name: name.into(),
+            reason: reason.into(),
+        }
+    }
+
+    /// Creates a new InvalidQuery error with the given message.
+    pub fn invalid_query(message: impl Into<String>) -> Self {
+        Self::InvalidQuery {
+            message: message.into(),
+        }
+    }
+
     /// Creates a new SystemError with the given message.
     pub fn system_error(message: impl Into<String>) -> Self {
         Self::SystemError {
@@ -54,21 +99,34 @@ impl PortsError {
         }
     }
 
+    /// Creates a new ConfigError with the given message.
+    pub fn config_error(message: impl Into<String>) -> Self {
+        Self::ConfigError {
+            message: message.into(),
+        }
+    }
+
     /// Returns a hint for how to resolve this error.
     pub fn hint(&self) -> &'static str {
         match self {
-            Self::PermissionDenied { .. } => {
-                "Try running with elevated privileges (sudo) to see all process details"
-            }
+            Self::PermissionDenied { hint, .. } => hint,
             Self::ProcessNotFound { .. } => {
                 "The process may have terminated. Try running the command again"
             }
+            Self::PortNotInUse { .. } => {
+                "Run `ports` to list the ports in use, or check --tcp/--udp"
+            }
+            Self::Protected { .. } => {
+                "Pass --force to stop it anyway, or allow it in ~/.config/ports/protection.toml"
+            }
+            Self::InvalidQuery { .. } => {
+                "Queries look like `type = node and port in 3000..3999`; quote values containing spaces"
+            }
             Self::SystemError { .. } => {
                 "Check system permissions and ensure the OS APIs are accessible"
             }
-            Self::IoError(_) => {
-                "Check file permissions and system resources"
-            }
+            Self::ConfigError { .. } => "Fix or remove the offending file in ~/.config/ports",
+            Self::IoError(_) => "Check file permissions and system resources",
         }
     }
 }
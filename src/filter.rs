@@ -0,0 +1,296 @@
+//! Filtering of listed entries.
+//!
+//! Filters are applied after detection, so they can select entries by
+//! application type as well as by port and process. All given filters must
+//! match; a filter given several values (`--type node,python`) matches any
+//! of them.
+
+use crate::app_detector::Glob;
+use crate::models::{AppType, PortEntry};
+use crate::query::Query;
+use regex::Regex;
+use std::fmt;
+use std::str::FromStr;
+
+/// An inclusive range of ports, written as `3000-3999`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PortRange {
+    /// First port of the range
+    pub start: u16,
+    /// Last port of the range
+    pub end: u16,
+}
+
+impl PortRange {
+    /// Returns true if the range contains `port`.
+    pub fn contains(&self, port: u16) -> bool {
+        (self.start..=self.end).contains(&port)
+    }
+}
+
+impl FromStr for PortRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (start, end) = s
+            .split_once('-')
+            .ok_or_else(|| format!("invalid port range '{}' (expected e.g. 3000-3999)", s))?;
+        let port = |text: &str| {
+            text.trim()
+                .parse::<u16>()
+                .map_err(|_| format!("invalid port '{}' in range '{}'", text, s))
+        };
+
+        let range = PortRange {
+            start: port(start)?,
+            end: port(end)?,
+        };
+        if range.start > range.end {
+            return Err(format!(
+                "invalid port range '{}' (the first port is greater than the last)",
+                s
+            ));
+        }
+        Ok(range)
+    }
+}
+
+impl fmt::Display for PortRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.start, self.end)
+    }
+}
+
+/// Conditions an entry must meet to be listed.
+///
+/// Empty conditions match every entry.
+#[derive(Debug, Clone, Default)]
+pub struct EntryFilter {
+    /// Port numbers
+    pub ports: Vec<u16>,
+    /// Port range
+    pub range: Option<PortRange>,
+    /// Application types or categories, matched by prefix
+    pub types: Vec<String>,
+    /// User name or UID of the process
+    pub user: Option<String>,
+    /// Process IDs
+    pub pids: Vec<u32>,
+    /// Process name globs
+    pub names: Vec<Glob>,
+    /// Regular expression matched against the command line
+    pub command: Option<Regex>,
+    /// `--where` query
+    pub query: Option<Query>,
+}
+
+impl EntryFilter {
+    /// Returns true if the entry meets every condition.
+    pub fn matches(&self, entry: &PortEntry) -> bool {
+        let process = &entry.process;
+
+        (self.ports.is_empty() || self.ports.contains(&entry.port))
+            && self.range.is_none_or(|range| range.contains(entry.port))
+            && (self.types.is_empty() || self.types.iter().any(|t| type_matches(entry, t)))
+            && self.user.as_ref().is_none_or(|user| {
+                process.user.as_ref() == Some(user)
+                    || process.uid.is_some_and(|uid| uid.to_string() == *user)
+            })
+            && (self.pids.is_empty() || self.pids.contains(&process.pid))
+            && (self.names.is_empty()
+                || self
+                    .names
+                    .iter()
+                    .any(|glob| glob.matches(&process.name.to_lowercase())))
+            && self.command.as_ref().is_none_or(|regex| {
+                regex.is_match(process.command.as_deref().unwrap_or(&process.name))
+            })
+            && self.query.as_ref().is_none_or(|query| query.matches(entry))
+    }
+
+    /// Keeps the entries that meet every condition.
+    pub fn apply(&self, entries: &mut Vec<PortEntry>) {
+        entries.retain(|entry| self.matches(entry));
+    }
+}
+
+/// Returns true if the application type of an entry matches a name given
+/// on the command line.
+///
+/// The name matches the start of the type's identifier or display name,
+/// ignoring case and punctuation (`node` matches `Node.js`), or names its
+/// category (`database`).
+pub fn type_matches(entry: &PortEntry, name: &str) -> bool {
+    let normalize = |text: &str| -> String {
+        text.chars()
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(char::to_lowercase)
+            .collect()
+    };
+    let name = normalize(name);
+    if name.is_empty() {
+        return false;
+    }
+
+    let app_type = entry.app_type.clone().unwrap_or(AppType::Unknown);
+    normalize(app_type.id()).starts_with(&name)
+        || normalize(app_type.display_name()).starts_with(&name)
+        || normalize(app_type.category().display_name()) == name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ProcessInfo, Protocol};
+
+    fn entries() -> Vec<PortEntry> {
+        let mut node = ProcessInfo::new(4242, "node")
+            .with_command("node server.js --port 3000")
+            .with_user("alice");
+        node.uid = Some(501);
+        let mut postgres = ProcessInfo::new(99, "postgres").with_user("postgres");
+        postgres.uid = Some(70);
+        let python = ProcessInfo::new(5151, "python3.12")
+            .with_command("python -m http.server 8000")
+            .with_user("alice");
+
+        vec![
+            PortEntry::new(3000, Protocol::Tcp, node).with_app_type(AppType::NodeJs),
+            PortEntry::new(5432, Protocol::Tcp, postgres).with_app_type(AppType::PostgreSql),
+            PortEntry::new(8000, Protocol::Tcp, python).with_app_type(AppType::Python),
+            PortEntry::new(9999, Protocol::Udp, ProcessInfo::new(1, "mystery")),
+        ]
+    }
+
+    fn ports(filter: &EntryFilter) -> Vec<u16> {
+        let mut entries = entries();
+        filter.apply(&mut entries);
+        entries.iter().map(|e| e.port).collect()
+    }
+
+    #[test]
+    fn test_parse_port_range() {
+        assert_eq!(
+            "3000-3999".parse::<PortRange>(),
+            Ok(PortRange {
+                start: 3000,
+                end: 3999
+            })
+        );
+        assert_eq!("80-80".parse::<PortRange>().unwrap().to_string(), "80-80");
+        assert!("3000".parse::<PortRange>().is_err());
+        assert!("3999-3000".parse::<PortRange>().is_err());
+        assert!("3000-70000".parse::<PortRange>().is_err());
+    }
+
+    #[test]
+    fn test_empty_filter_matches_everything() {
+        assert_eq!(ports(&EntryFilter::default()), vec![3000, 5432, 8000, 9999]);
+    }
+
+    #[test]
+    fn test_single_filters() {
+        let cases = [
+            (
+                EntryFilter {
+                    ports: vec![5432, 8000],
+                    ..Default::default()
+                },
+                vec![5432, 8000],
+            ),
+            (
+                EntryFilter {
+                    range: Some("3000-5999".parse().unwrap()),
+                    ..Default::default()
+                },
+                vec![3000, 5432],
+            ),
+            (
+                EntryFilter {
+                    types: vec!["node".to_string(), "Python".to_string()],
+                    ..Default::default()
+                },
+                vec![3000, 8000],
+            ),
+            (
+                EntryFilter {
+                    types: vec!["database".to_string()],
+                    ..Default::default()
+                },
+                vec![5432],
+            ),
+            (
+                EntryFilter {
+                    types: vec!["unknown".to_string()],
+                    ..Default::default()
+                },
+                vec![9999],
+            ),
+            (
+                EntryFilter {
+                    user: Some("alice".to_string()),
+                    ..Default::default()
+                },
+                vec![3000, 8000],
+            ),
+            (
+                EntryFilter {
+                    user: Some("70".to_string()),
+                    ..Default::default()
+                },
+                vec![5432],
+            ),
+            (
+                EntryFilter {
+                    pids: vec![99],
+                    ..Default::default()
+                },
+                vec![5432],
+            ),
+            (
+                EntryFilter {
+                    names: vec![Glob::new("Python*"), Glob::new("mystery")],
+                    ..Default::default()
+                },
+                vec![8000, 9999],
+            ),
+            (
+                EntryFilter {
+                    // Entries without a command line are matched by name
+                    command: Some(Regex::new(r"server|^post").unwrap()),
+                    ..Default::default()
+                },
+                vec![3000, 5432, 8000],
+            ),
+        ];
+
+        for (filter, expected) in cases {
+            assert_eq!(ports(&filter), expected, "{:?}", filter);
+        }
+    }
+
+    #[test]
+    fn test_filters_are_combined() {
+        let filter = EntryFilter {
+            user: Some("alice".to_string()),
+            command: Some(Regex::new("server").unwrap()),
+            range: Some("8000-8999".parse().unwrap()),
+            ..Default::default()
+        };
+        assert_eq!(ports(&filter), vec![8000]);
+
+        let filter = EntryFilter {
+            types: vec!["node".to_string()],
+            pids: vec![99],
+            ..Default::default()
+        };
+        assert!(ports(&filter).is_empty());
+
+        let filter = EntryFilter {
+            user: Some("alice".to_string()),
+            query: Some("port < 5000 or type = database".parse().unwrap()),
+            ..Default::default()
+        };
+        assert_eq!(ports(&filter), vec![3000]);
+    }
+}
@@ -0,0 +1,379 @@
+//! Stopping the process that holds a port.
+//!
+//! A process is asked to exit with a signal (SIGTERM by default), then
+//! given a grace period to release its port. Processes that ignore the
+//! signal can optionally be escalated to SIGKILL.
+
+use crate::error::{PortsError, Result};
+use crate::models::PortEntry;
+use crate::protection::OTHER_USER_HINT;
+use serde::{Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Signals that can be sent with `ports kill`.
+const SIGNALS: &[(&str, libc::c_int)] = &[
+    ("HUP", libc::SIGHUP),
+    ("INT", libc::SIGINT),
+    ("QUIT", libc::SIGQUIT),
+    ("KILL", libc::SIGKILL),
+    ("USR1", libc::SIGUSR1),
+    ("USR2", libc::SIGUSR2),
+    ("TERM", libc::SIGTERM),
+];
+
+/// How often the port is checked while waiting for it to be released.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A signal that can be sent to a process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Signal {
+    name: &'static str,
+    number: libc::c_int,
+}
+
+impl Signal {
+    /// SIGTERM, the default signal.
+    pub const TERM: Signal = Signal {
+        name: "TERM",
+        number: libc::SIGTERM,
+    };
+
+    /// SIGKILL, which cannot be caught or ignored.
+    pub const KILL: Signal = Signal {
+        name: "KILL",
+        number: libc::SIGKILL,
+    };
+}
+
+impl FromStr for Signal {
+    type Err = String;
+
+    /// Parses a signal name (`TERM`, `SIGTERM`, case-insensitive) or
+    /// number (`15`).
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let upper = s.to_uppercase();
+        let name = upper.strip_prefix("SIG").unwrap_or(&upper);
+        let number: Option<libc::c_int> = s.parse().ok();
+
+        SIGNALS
+            .iter()
+            .find(|(n, num)| *n == name || Some(*num) == number)
+            .map(|&(name, number)| Signal { name, number })
+            .ok_or_else(|| {
+                let names: Vec<&str> = SIGNALS.iter().map(|(n, _)| *n).collect();
+                format!(
+                    "unknown signal '{}' (expected one of {})",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+impl fmt::Display for Signal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SIG{}", self.name)
+    }
+}
+
+impl Serialize for Signal {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Settings for stopping a process.
+#[derive(Debug, Clone)]
+pub struct KillOptions {
+    /// Signal sent first
+    pub signal: Signal,
+    /// How long to wait for the port to be released after each signal
+    pub timeout: Duration,
+    /// Send SIGKILL if the port is still held after the timeout
+    pub escalate: bool,
+    /// Report what would be done without sending any signal
+    pub dry_run: bool,
+}
+
+/// What happened to a process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KillOutcome {
+    /// No signal was sent (dry run)
+    DryRun,
+    /// The port was released after the first signal
+    Released,
+    /// The port was released after escalating to SIGKILL
+    Killed,
+    /// The port was still held when the timeout expired
+    StillRunning,
+    /// The process exited before any signal was sent
+    AlreadyExited,
+}
+
+impl KillOutcome {
+    /// Returns true if the port is no longer held by the process.
+    pub fn is_stopped(&self) -> bool {
+        matches!(
+            self,
+            KillOutcome::Released | KillOutcome::Killed | KillOutcome::AlreadyExited
+        )
+    }
+}
+
+/// The result of stopping a single process.
+#[derive(Debug, Clone, Serialize)]
+pub struct KillResult {
+    /// Process ID
+    pub pid: u32,
+    /// Process name
+    pub name: String,
+    /// Signals sent, in order
+    pub signals_sent: Vec<Signal>,
+    /// What happened to the process
+    pub outcome: KillOutcome,
+    /// Why the process is protected, if it was stopped with `--force`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protection: Option<String>,
+}
+
+/// The result of `ports kill`.
+#[derive(Debug, Clone, Serialize)]
+pub struct KillReport {
+    /// The port whose owners were stopped
+    pub port: u16,
+    /// Entries of the port, as listed before stopping
+    pub targets: Vec<PortEntry>,
+    /// Whether this was a dry run
+    pub dry_run: bool,
+    /// One result per process, in the order of `targets`
+    pub results: Vec<KillResult>,
+}
+
+impl KillReport {
+    /// Returns true if every process released the port (or would have been
+    /// signalled, for a dry run).
+    pub fn is_complete(&self) -> bool {
+        self.dry_run || self.results.iter().all(|r| r.outcome.is_stopped())
+    }
+}
+
+/// Returns the first entry of each process holding a port.
+pub fn target_processes(targets: &[PortEntry]) -> Vec<&PortEntry> {
+    let mut processes: Vec<&PortEntry> = Vec::new();
+    for entry in targets {
+        if !processes.iter().any(|e| e.process.pid == entry.process.pid) {
+            processes.push(entry);
+        }
+    }
+    processes
+}
+
+/// Stops a process and waits until `is_released` reports that it no longer
+/// holds the port.
+pub fn stop_process(
+    pid: u32,
+    name: &str,
+    options: &KillOptions,
+    mut is_released: impl FnMut() -> Result<bool>,
+) -> Result<KillResult> {
+    let mut result = KillResult {
+        pid,
+        name: name.to_string(),
+        signals_sent: Vec::new(),
+        outcome: KillOutcome::DryRun,
+        protection: None,
+    };
+
+    if options.dry_run {
+        result.signals_sent.push(options.signal);
+        return Ok(result);
+    }
+
+    if !send_signal(pid, options.signal)? {
+        result.outcome = KillOutcome::AlreadyExited;
+        return Ok(result);
+    }
+    result.signals_sent.push(options.signal);
+
+    if wait_until_released(options.timeout, &mut is_released)? {
+        result.outcome = KillOutcome::Released;
+        return Ok(result);
+    }
+
+    result.outcome = KillOutcome::StillRunning;
+    if options.escalate && options.signal != Signal::KILL {
+        // The process may exit between the timeout and SIGKILL
+        if send_signal(pid, Signal::KILL)? {
+            result.signals_sent.push(Signal::KILL);
+        }
+        if wait_until_released(options.timeout, &mut is_released)? {
+            result.outcome = KillOutcome::Killed;
+        }
+    }
+
+    Ok(result)
+}
+
+/// Sends a signal to a process.
+///
+/// Returns false if the process no longer exists.
+pub fn send_signal(pid: u32, signal: Signal) -> Result<bool> {
+    // PID 0 and negative PIDs address whole process groups
+    let pid = match libc::pid_t::try_from(pid) {
+        Ok(pid) if pid > 0 => pid,
+        _ => return Err(PortsError::system_error(format!("Invalid PID {}", pid))),
+    };
+
+    // SAFETY: kill has no memory safety requirements
+    if unsafe { libc::kill(pid, signal.number) } == 0 {
+        return Ok(true);
+    }
+
+    let error = std::io::Error::last_os_error();
+    match error.raw_os_error() {
+        Some(libc::ESRCH) => Ok(false),
+        Some(libc::EPERM) => Err(PortsError::permission_denied_with_hint(
+            format!("Cannot send {} to PID {}", signal, pid),
+            OTHER_USER_HINT,
+        )),
+        _ => Err(PortsError::system_error(format!(
+            "Failed to send {} to PID {}: {}",
+            signal, pid, error
+        ))),
+    }
+}
+
+/// Polls `is_released` until it returns true or the timeout expires.
+fn wait_until_released(
+    timeout: Duration,
+    is_released: &mut impl FnMut() -> Result<bool>,
+) -> Result<bool> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if is_released()? {
+            return Ok(true);
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return Ok(false);
+        }
+        thread::sleep(POLL_INTERVAL.min(deadline - now));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ProcessInfo, Protocol};
+    use std::process::{Child, Command};
+
+    fn options(timeout_ms: u64, escalate: bool) -> KillOptions {
+        KillOptions {
+            signal: Signal::TERM,
+            timeout: Duration::from_millis(timeout_ms),
+            escalate,
+            dry_run: false,
+        }
+    }
+
+    fn spawn(script: &str) -> Child {
+        let child = Command::new("sh").arg("-c").arg(script).spawn().unwrap();
+        // Give the shell time to install its traps
+        thread::sleep(Duration::from_millis(200));
+        child
+    }
+
+    /// The child is a zombie after exiting until it is reaped, so "released"
+    /// is checked by reaping it.
+    fn has_exited(child: &mut Child) -> Result<bool> {
+        Ok(child.try_wait()?.is_some())
+    }
+
+    #[test]
+    fn test_parse_signal() {
+        assert_eq!("TERM".parse::<Signal>(), Ok(Signal::TERM));
+        assert_eq!("sigterm".parse::<Signal>(), Ok(Signal::TERM));
+        assert_eq!("9".parse::<Signal>(), Ok(Signal::KILL));
+        assert_eq!("INT".parse::<Signal>().unwrap().to_string(), "SIGINT");
+        assert!("STOP".parse::<Signal>().is_err());
+        assert!("0".parse::<Signal>().is_err());
+    }
+
+    #[test]
+    fn test_target_processes() {
+        let entry =
+            |port, pid, name| PortEntry::new(port, Protocol::Tcp, ProcessInfo::new(pid, name));
+        let targets = vec![
+            entry(3000, 10, "node"),
+            entry(3000, 10, "node"),
+            entry(3000, 11, "node"),
+        ];
+
+        let pids: Vec<u32> = target_processes(&targets)
+            .iter()
+            .map(|e| e.process.pid)
+            .collect();
+        assert_eq!(pids, vec![10, 11]);
+    }
+
+    #[test]
+    fn test_stop_process() {
+        let mut child = spawn("sleep 30");
+        let result = stop_process(child.id(), "sh", &options(2000, false), || {
+            has_exited(&mut child)
+        })
+        .unwrap();
+
+        assert_eq!(result.outcome, KillOutcome::Released);
+        assert_eq!(result.signals_sent, vec![Signal::TERM]);
+    }
+
+    #[test]
+    fn test_escalate_to_sigkill() {
+        let mut child = spawn("trap '' TERM; while :; do sleep 1; done");
+
+        let result = stop_process(child.id(), "sh", &options(300, false), || {
+            has_exited(&mut child)
+        })
+        .unwrap();
+        assert_eq!(result.outcome, KillOutcome::StillRunning);
+
+        let result = stop_process(child.id(), "sh", &options(300, true), || {
+            has_exited(&mut child)
+        })
+        .unwrap();
+        assert_eq!(result.outcome, KillOutcome::Killed);
+        assert_eq!(result.signals_sent, vec![Signal::TERM, Signal::KILL]);
+    }
+
+    #[test]
+    fn test_dry_run_sends_nothing() {
+        let mut child = spawn("sleep 30");
+        let options = KillOptions {
+            dry_run: true,
+            ..options(100, true)
+        };
+
+        let result = stop_process(child.id(), "sh", &options, || has_exited(&mut child)).unwrap();
+        assert_eq!(result.outcome, KillOutcome::DryRun);
+        assert!(!has_exited(&mut child).unwrap());
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+    }
+
+    #[test]
+    fn test_already_exited() {
+        let mut child = Command::new("true").spawn().unwrap();
+        let pid = child.id();
+        child.wait().unwrap();
+
+        let result = stop_process(pid, "true", &options(100, false), || Ok(true)).unwrap();
+        assert_eq!(result.outcome, KillOutcome::AlreadyExited);
+        assert!(result.signals_sent.is_empty());
+    }
+}
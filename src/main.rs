@@ -1,46 +1,283 @@
-//! ports - Display listening TCP ports and application information
+//! ports - Display listening TCP/UDP ports and application information
 //!
 //! A CLI tool for macOS that shows which applications are using which ports,
 //! helping developers manage their local development environment.
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use regex::Regex;
+use std::io::{self, BufRead, Write};
 use std::process::ExitCode;
+use std::thread;
+use std::time::Duration;
 
 mod app_detector;
+mod audit;
+mod config;
 mod error;
+mod filter;
+mod kill;
 mod models;
 mod output;
+mod port_registry;
 mod port_scanner;
 mod process_info;
+mod protection;
+mod query;
+mod sort;
+mod tui;
+mod watch;
 
+use app_detector::{Glob, ManifestCache};
+use audit::AuditPolicy;
 use error::PortsError;
-use output::OutputFormat;
+use filter::{EntryFilter, PortRange};
+use kill::{KillOptions, KillReport, Signal};
+use models::{PortEntry, Protocol};
+use output::{Column, OutputFormat, TableLayout};
+use port_registry::PortRegistry;
+use port_scanner::BackendKind;
+use protection::ProtectionPolicy;
+use sort::{GroupBy, SortKey};
 
-/// Display listening TCP ports and application information.
+/// Display listening TCP/UDP ports and application information.
 ///
 /// Shows which ports are in use, what applications are using them,
 /// and provides information to help decide if a process can be stopped.
 #[derive(Parser, Debug)]
 #[command(name = "ports")]
 #[command(version)]
-#[command(about = "リッスン中のTCPポートとアプリケーション情報を表示")]
+#[command(about = "リッスン中のTCP/UDPポートとアプリケーション情報を表示")]
 #[command(
-    long_about = "リッスン中のTCPポートとアプリケーション情報を表示します。\n\n\
+    long_about = "リッスン中のTCP/UDPポートとアプリケーション情報を表示します。\n\n\
     開発者がローカル環境でどのポートがどのアプリケーションに\n\
     使用されているかを確認し、プロセスを停止してよいかを\n\
     判断するための情報を提供します。"
 )]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Output in JSON format for scripting and automation
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     json: bool,
+
+    /// Show only TCP ports (combine with --udp to show both)
+    #[arg(long, global = true)]
+    tcp: bool,
+
+    /// Show only UDP ports (combine with --tcp to show both)
+    #[arg(long, global = true)]
+    udp: bool,
+
+    /// Port scanning backend (falls back to the others, with a warning, if
+    /// it is unavailable or fails)
+    #[arg(long, value_enum, default_value_t = BackendKind::Auto, global = true)]
+    backend: BackendKind,
+
+    /// Show which detection rules matched each process
+    #[arg(long)]
+    explain: bool,
+
+    /// Show only these ports (comma-separated)
+    #[arg(long, value_name = "PORTS", value_delimiter = ',', num_args = 1..)]
+    port: Vec<u16>,
+
+    /// Show only ports in a range (e.g. 3000-3999)
+    #[arg(long, value_name = "START-END")]
+    range: Option<PortRange>,
+
+    /// Show only these application types or categories (comma-separated,
+    /// e.g. node,python,database; matched by prefix)
+    #[arg(long = "type", value_name = "TYPES", value_delimiter = ',', num_args = 1..)]
+    app_type: Vec<String>,
+
+    /// Show only processes of a user (name or UID)
+    #[arg(long)]
+    user: Option<String>,
+
+    /// Show only these process IDs (comma-separated)
+    #[arg(long, value_name = "PIDS", value_delimiter = ',', num_args = 1..)]
+    pid: Vec<u32>,
+
+    /// Show only processes whose name matches (comma-separated globs,
+    /// case-insensitive)
+    #[arg(long, value_name = "NAMES", value_delimiter = ',', num_args = 1..)]
+    name: Vec<String>,
+
+    /// Show only processes whose command line matches a regular expression
+    #[arg(long, value_name = "REGEX")]
+    grep: Option<Regex>,
+
+    /// Show only entries matching a query, e.g.
+    /// "(type = python or type = node) and uptime > 1d"
+    #[arg(long = "where", value_name = "QUERY")]
+    query: Option<String>,
+
+    /// Sort by these keys, each breaking ties of the previous one
+    /// (comma-separated, e.g. type,port)
+    #[arg(
+        long,
+        value_enum,
+        value_name = "KEYS",
+        value_delimiter = ',',
+        num_args = 1..,
+        default_value = "port"
+    )]
+    sort: Vec<SortKey>,
+
+    /// Reverse the sort order
+    #[arg(short, long)]
+    reverse: bool,
+
+    /// Group the table by application type or user, with a heading per
+    /// group (not available with --watch)
+    #[arg(long, value_enum, value_name = "KEY", conflicts_with = "watch")]
+    group_by: Option<GroupBy>,
+
+    /// Output style (wide shows more columns and full command lines)
+    #[arg(
+        short,
+        long,
+        value_enum,
+        value_name = "FORMAT",
+        default_value_t = OutputStyle::Table
+    )]
+    output: OutputStyle,
+
+    /// Table columns to show, in order (comma-separated, e.g.
+    /// port,pid,user,type,address,started,command)
+    #[arg(
+        long,
+        value_name = "COLUMNS",
+        value_delimiter = ',',
+        num_args = 1..,
+        value_parser = output::parse_column
+    )]
+    columns: Vec<&'static Column>,
+
+    /// Do not print the table header
+    #[arg(long)]
+    no_header: bool,
+
+    /// Keep refreshing the list and highlight ports that opened or closed
+    /// (with --json, print one change event per line)
+    #[arg(short, long)]
+    watch: bool,
+
+    /// How often to refresh with --watch (e.g. 2s, 500ms)
+    #[arg(
+        long,
+        value_name = "DURATION",
+        default_value = "2s",
+        value_parser = parse_interval,
+        requires = "watch"
+    )]
+    interval: Duration,
+}
+
+/// Output style selected with `--output`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum OutputStyle {
+    /// Table of the most useful columns
+    Table,
+    /// Table of more columns, without truncating long values
+    Wide,
+    /// JSON, the same as --json
+    Json,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Check for services reachable from other machines
+    ///
+    /// Classifies each port as loopback-only, LAN-exposed or bound to all
+    /// interfaces, and exits with status 3 if a port that must never be
+    /// public is reachable from the network.
+    Audit(AuditArgs),
+
+    /// Stop the process listening on a port
+    ///
+    /// Shows the process, asks for confirmation and sends SIGTERM, then
+    /// waits for the port to be released. Exits with status 4 if the
+    /// process still holds the port after the timeout. System daemons,
+    /// shared services and processes of other users are protected and
+    /// require --force.
+    Kill(KillArgs),
+
+    /// Browse listening ports in an interactive full-screen view
+    ///
+    /// The list refreshes itself periodically. Search with /, sort with s,
+    /// and stop the selected process with x.
+    Tui(TuiArgs),
+}
+
+#[derive(clap::Args, Debug)]
+struct AuditArgs {
+    /// Ports that must only listen on loopback (replaces the default list)
+    #[arg(long, value_name = "PORTS", value_delimiter = ',', num_args = 1..)]
+    never_public: Option<Vec<u16>>,
+}
+
+#[derive(clap::Args, Debug)]
+struct KillArgs {
+    /// Port whose process should be stopped
+    port: u16,
+
+    /// Do not ask for confirmation
+    #[arg(short, long)]
+    yes: bool,
+
+    /// Signal to send first (name such as TERM or INT, or number)
+    #[arg(short, long, default_value_t = Signal::TERM)]
+    signal: Signal,
+
+    /// How long to wait for the port to be released (e.g. 5s, 500ms)
+    #[arg(short, long, default_value = "5s", value_parser = parse_duration)]
+    timeout: Duration,
+
+    /// Send SIGKILL if the port is still in use after the timeout
+    #[arg(long)]
+    escalate: bool,
+
+    /// Show what would be stopped without sending any signal
+    #[arg(short = 'n', long)]
+    dry_run: bool,
+
+    /// Stop the process even if it is protected
+    #[arg(short, long)]
+    force: bool,
 }
 
+#[derive(clap::Args, Debug)]
+struct TuiArgs {
+    /// How often to refresh the list (e.g. 2s, 500ms)
+    #[arg(long, value_name = "DURATION", default_value = "2s", value_parser = parse_interval)]
+    interval: Duration,
+}
+
+/// Shortest refresh interval of `--watch` and `ports tui`, so that a zero
+/// interval does not rescan in a busy loop.
+const MIN_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Exit status of `ports audit` when violations are found.
+const EXIT_AUDIT_VIOLATIONS: u8 = 3;
+
+/// Exit status of `ports kill` when a process still holds the port.
+const EXIT_KILL_INCOMPLETE: u8 = 4;
+
 fn main() -> ExitCode {
     let args = Args::parse();
 
-    match run(&args) {
-        Ok(()) => ExitCode::SUCCESS,
+    let result = match &args.command {
+        None if args.watch => run_watch(&args).map(|()| ExitCode::SUCCESS),
+        None => run(&args).map(|()| ExitCode::SUCCESS),
+        Some(Command::Audit(audit_args)) => run_audit(&args, audit_args),
+        Some(Command::Kill(kill_args)) => run_kill(&args, kill_args),
+        Some(Command::Tui(tui_args)) => run_tui(&args, tui_args).map(|()| ExitCode::SUCCESS),
+    };
+
+    match result {
+        Ok(code) => code,
         Err(error) => {
             eprintln!("error: {}", error);
             eprintln!("hint: {}", error.hint());
@@ -50,34 +287,391 @@ fn main() -> ExitCode {
 }
 
 fn run(args: &Args) -> Result<(), PortsError> {
+    let rules = app_detector::RuleSet::load()?;
+    let registry = PortRegistry::load(&rules)?;
+    let filter = entry_filter(args)?;
+    let entries = collect_entries(args, &rules, &registry, &filter, &mut ManifestCache::new())?;
+
+    match args.group_by {
+        Some(group_by) if output_format(args) == OutputFormat::Table => {
+            output::print_grouped_table(&entries, group_by, &table_layout(args))
+        }
+        _ => output::print_entries(&entries, output_format(args), &table_layout(args)),
+    }
+
+    // The JSON output already carries the matched rule of each entry
+    if args.explain && output_format(args) == OutputFormat::Table {
+        let explanations: Vec<_> = entries
+            .iter()
+            .map(|entry| (entry, rules.explain(&entry.process)))
+            .collect();
+        output::print_explanations(&explanations);
+    }
+
+    Ok(())
+}
+
+/// Runs `ports --watch` until interrupted.
+fn run_watch(args: &Args) -> Result<(), PortsError> {
+    let rules = app_detector::RuleSet::load()?;
+    let registry = PortRegistry::load(&rules)?;
+    let filter = entry_filter(args)?;
+    let layout = table_layout(args);
+    // The ports file and the manifests are only read once for all ticks
+    let mut manifests = ManifestCache::new();
+    let mut previous: Option<Vec<PortEntry>> = None;
+
+    loop {
+        // A failed scan is often transient, so keep the previous list and
+        // try again on the next tick, as the TUI does
+        let entries = match collect_entries(args, &rules, &registry, &filter, &mut manifests) {
+            Ok(entries) => entries,
+            Err(e) => {
+                output::print_warning(&format!("Failed to refresh the list: {}", e));
+                thread::sleep(args.interval);
+                continue;
+            }
+        };
+        let changes = watch::diff(previous.as_deref().unwrap_or_default(), &entries);
+
+        match output_format(args) {
+            // Everything would be new on the first frame
+            OutputFormat::Table if previous.is_none() => output::print_watch_frame(
+                &entries,
+                &[],
+                args.interval,
+                &layout,
+                &args.sort,
+                args.reverse,
+            ),
+            OutputFormat::Table => output::print_watch_frame(
+                &entries,
+                &changes,
+                args.interval,
+                &layout,
+                &args.sort,
+                args.reverse,
+            ),
+            OutputFormat::Json => output::print_change_events(&changes),
+        }
+
+        previous = Some(entries);
+        thread::sleep(args.interval);
+    }
+}
+
+/// Runs `ports tui` until the user quits.
+fn run_tui(args: &Args, tui_args: &TuiArgs) -> Result<(), PortsError> {
+    let rules = app_detector::RuleSet::load()?;
+    let registry = PortRegistry::load(&rules)?;
+    let filter = entry_filter(args)?;
+    let mut manifests = ManifestCache::new();
+    tui::run(tui_args.interval, || {
+        collect_entries(args, &rules, &registry, &filter, &mut manifests)
+    })
+}
+
+/// Runs `ports audit` and returns the exit status.
+fn run_audit(args: &Args, audit_args: &AuditArgs) -> Result<ExitCode, PortsError> {
+    let rules = app_detector::RuleSet::load()?;
+    let registry = PortRegistry::load(&rules)?;
+    let filter = entry_filter(args)?;
+    let entries = collect_entries(args, &rules, &registry, &filter, &mut ManifestCache::new())?;
+
+    let mut policy = AuditPolicy::default();
+    if let Some(ports) = &audit_args.never_public {
+        policy.never_public = ports.clone();
+    }
+
+    let report = audit::audit_entries(entries, &policy);
+    output::print_audit_report(&report, output_format(args));
+
+    if report.has_violations() {
+        Ok(ExitCode::from(EXIT_AUDIT_VIOLATIONS))
+    } else {
+        Ok(ExitCode::SUCCESS)
+    }
+}
+
+/// Runs `ports kill` and returns the exit status.
+fn run_kill(args: &Args, kill_args: &KillArgs) -> Result<ExitCode, PortsError> {
+    let rules = app_detector::RuleSet::load()?;
+    let registry = PortRegistry::load(&rules)?;
+    let filter = entry_filter(args)?;
+    let mut targets = collect_entries(args, &rules, &registry, &filter, &mut ManifestCache::new())?;
+    targets.retain(|e| e.port == kill_args.port);
+    if targets.is_empty() {
+        return Err(PortsError::port_not_in_use(kill_args.port));
+    }
+
+    let options = KillOptions {
+        signal: kill_args.signal,
+        timeout: kill_args.timeout,
+        escalate: kill_args.escalate,
+        dry_run: kill_args.dry_run,
+    };
+    let format = output_format(args);
+
+    if format == OutputFormat::Table {
+        output::print_entries(&targets, format, &TableLayout::default());
+        println!();
+    }
+
+    // Check every process before stopping any of them
+    let policy = ProtectionPolicy::load()?;
+    let mut processes = Vec::new();
+    for entry in kill::target_processes(&targets) {
+        let process = &entry.process;
+        policy.check_permission(process)?;
+
+        let protection = policy.check(entry).map(|p| p.reason());
+        if let Some(reason) = &protection {
+            if !kill_args.force {
+                return Err(PortsError::protected(process.pid, &process.name, reason));
+            }
+            output::print_warning(&format!(
+                "PID {} ({}) is protected because {}; stopping it because of --force",
+                process.pid, process.name, reason
+            ));
+        }
+        processes.push((process.pid, process.name.clone(), protection));
+    }
+
+    if !options.dry_run && !kill_args.yes && !confirm_kill(&processes, options.signal)? {
+        eprintln!("中止しました");
+        return Ok(ExitCode::from(1));
+    }
+
+    let mut results = Vec::new();
+    for (pid, name, protection) in processes {
+        let protocols: Vec<Protocol> = targets
+            .iter()
+            .filter(|e| e.process.pid == pid)
+            .map(|e| e.protocol)
+            .collect();
+
+        // The port is released once a fresh scan no longer shows the
+        // process holding it
+        let mut result = kill::stop_process(pid, &name, &options, || {
+            let entries = port_scanner::scan_listening_ports(args.backend)?;
+            Ok(!entries.iter().any(|e| {
+                e.port == kill_args.port && e.process.pid == pid && protocols.contains(&e.protocol)
+            }))
+        })?;
+        result.protection = protection;
+        results.push(result);
+    }
+
+    let report = KillReport {
+        port: kill_args.port,
+        targets,
+        dry_run: options.dry_run,
+        results,
+    };
+    output::print_kill_report(&report, &options, format);
+
+    if report.is_complete() {
+        Ok(ExitCode::SUCCESS)
+    } else {
+        Ok(ExitCode::from(EXIT_KILL_INCOMPLETE))
+    }
+}
+
+/// Asks on the terminal whether the processes should be stopped.
+///
+/// Anything other than "y" or "yes", including end of input, declines.
+fn confirm_kill(
+    processes: &[(u32, String, Option<String>)],
+    signal: Signal,
+) -> Result<bool, PortsError> {
+    let processes: Vec<String> = processes
+        .iter()
+        .map(|(pid, name, _)| format!("PID {} ({})", pid, name))
+        .collect();
+    eprint!(
+        "{} に {} を送信しますか? [y/N] ",
+        processes.join(", "),
+        signal
+    );
+    io::stderr().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Parses a duration such as `5s`, `500ms`, `1m` or `2` (seconds).
+fn parse_duration(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(s.len());
+    let (value, unit) = s.split_at(split);
+
+    let value: f64 = value
+        .parse()
+        .map_err(|_| format!("invalid duration '{}'", s))?;
+    let seconds = match unit {
+        "" | "s" => value,
+        "ms" => value / 1000.0,
+        "m" => value * 60.0,
+        _ => {
+            return Err(format!(
+                "invalid duration unit '{}' (expected ms, s or m)",
+                unit
+            ))
+        }
+    };
+
+    Duration::try_from_secs_f64(seconds).map_err(|_| format!("invalid duration '{}'", s))
+}
+
+/// Parses a refresh interval, which must be at least [`MIN_INTERVAL`].
+fn parse_interval(s: &str) -> Result<Duration, String> {
+    let interval = parse_duration(s)?;
+    if interval < MIN_INTERVAL {
+        return Err(format!(
+            "interval '{}' is too short (the minimum is {}ms)",
+            s.trim(),
+            MIN_INTERVAL.as_millis()
+        ));
+    }
+    Ok(interval)
+}
+
+/// Scans listening ports, fills in process details and application types,
+/// and keeps the entries selected by the filters.
+fn collect_entries(
+    args: &Args,
+    rules: &app_detector::RuleSet,
+    registry: &PortRegistry,
+    filter: &EntryFilter,
+    manifests: &mut ManifestCache,
+) -> Result<Vec<PortEntry>, PortsError> {
     // Scan for listening ports
-    let mut entries = port_scanner::scan_listening_ports()?;
+    let mut entries = port_scanner::scan_listening_ports(args.backend)?;
 
-    // Sort by port number (ascending)
-    entries.sort_by_key(|e| e.port);
+    // Keep only the requested protocols
+    entries.retain(|e| protocol_selected(args, e.protocol));
 
-    // Enrich with detailed process info
-    for entry in &mut entries {
-        if let Err(e) = process_info::enrich_process_info(&mut entry.process) {
-            // Log warning but continue with partial info
-            eprintln!("warning: Failed to get details for PID {}: {}", entry.process.pid, e);
+    // Sort by port number (ascending), TCP before UDP
+    entries.sort_by_key(|e| (e.port, e.protocol));
+
+    // Enrich with detailed process info, looked up for all PIDs at once
+    let pids: Vec<u32> = entries.iter().map(|e| e.process.pid).collect();
+    match process_info::lookup_process_details(&pids) {
+        Ok(details) => {
+            for entry in &mut entries {
+                match details.get(&entry.process.pid) {
+                    Some(details) => details.apply_to(&mut entry.process),
+                    // Log warning but continue with partial info
+                    None => output::print_warning(&format!(
+                        "Failed to get details for PID {}: {}",
+                        entry.process.pid,
+                        PortsError::process_not_found(entry.process.pid)
+                    )),
+                }
+            }
         }
+        Err(e) => {
+            output::print_warning(&format!("Failed to get process details: {}", e));
+        }
+    }
+
+    // Detect application types, frameworks and runtime versions, checking
+    // the command line against the project manifests
+    for entry in &mut entries {
+        let detection = rules.detect(&entry.process);
+        entry.set_detection(manifests.refine(&entry.process, detection));
+        entry.runtime_version = app_detector::runtime_version(&entry.process);
     }
 
-    // Detect application types
+    // Annotate the services usually found on each port
     for entry in &mut entries {
-        let app_type = app_detector::detect_app_type(&entry.process);
-        entry.app_type = Some(app_type);
+        registry.annotate(entry);
     }
 
-    // Output results
-    let format = if args.json {
+    // Apply the filters and the requested order last, as both can use the
+    // application type
+    filter.apply(&mut entries);
+    sort::sort_entries(&mut entries, &args.sort, args.reverse, args.group_by);
+
+    Ok(entries)
+}
+
+/// Returns the filters selected on the command line.
+fn entry_filter(args: &Args) -> Result<EntryFilter, PortsError> {
+    Ok(EntryFilter {
+        ports: args.port.clone(),
+        range: args.range,
+        types: args.app_type.clone(),
+        user: args.user.clone(),
+        pids: args.pid.clone(),
+        names: args.name.iter().map(|name| Glob::new(name)).collect(),
+        command: args.grep.clone(),
+        query: args.query.as_deref().map(str::parse).transpose()?,
+    })
+}
+
+/// Returns the output format selected on the command line.
+fn output_format(args: &Args) -> OutputFormat {
+    if args.json || args.output == OutputStyle::Json {
         OutputFormat::Json
     } else {
         OutputFormat::Table
+    }
+}
+
+/// Returns the table layout selected on the command line.
+fn table_layout(args: &Args) -> TableLayout {
+    let mut layout = match args.output {
+        OutputStyle::Wide => TableLayout::wide(),
+        OutputStyle::Table | OutputStyle::Json => TableLayout::default(),
     };
+    if !args.columns.is_empty() {
+        layout.columns = args.columns.clone();
+    }
+    layout.header = !args.no_header;
+    layout
+}
 
-    output::print_entries(&entries, format);
+/// Returns true if entries of `protocol` should be shown.
+///
+/// Without `--tcp` or `--udp` both protocols are shown.
+fn protocol_selected(args: &Args, protocol: Protocol) -> bool {
+    if !args.tcp && !args.udp {
+        return true;
+    }
 
-    Ok(())
+    match protocol {
+        Protocol::Tcp => args.tcp,
+        Protocol::Udp => args.udp,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("5"), Ok(Duration::from_secs(5)));
+        assert_eq!(parse_duration("5s"), Ok(Duration::from_secs(5)));
+        assert_eq!(parse_duration("1.5s"), Ok(Duration::from_millis(1500)));
+        assert_eq!(parse_duration("500ms"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_duration("2m"), Ok(Duration::from_secs(120)));
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("5h").is_err());
+        assert!(parse_duration("s").is_err());
+    }
+
+    #[test]
+    fn test_parse_interval() {
+        assert_eq!(parse_interval("100ms"), Ok(MIN_INTERVAL));
+        assert_eq!(parse_interval("2s"), Ok(Duration::from_secs(2)));
+        for zero in ["0", "0s", "0ms"] {
+            assert!(parse_interval(zero).unwrap_err().contains("too short"));
+        }
+        assert!(parse_interval("99ms").is_err());
+    }
 }
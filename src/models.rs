@@ -4,26 +4,70 @@
 //! port entries, process information, and application types.
 
 use chrono::{DateTime, Utc};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+use std::path::PathBuf;
 use std::time::Duration;
 
 /// Network protocol type.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
 pub enum Protocol {
     /// TCP protocol
     Tcp,
+    /// UDP protocol
+    Udp,
 }
 
 impl std::fmt::Display for Protocol {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Protocol::Tcp => write!(f, "TCP"),
+            Protocol::Udp => write!(f, "UDP"),
+        }
+    }
+}
+
+/// IP address family of a socket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub enum IpFamily {
+    /// IPv4
+    V4,
+    /// IPv6 (including dual-stack sockets)
+    V6,
+}
+
+impl IpFamily {
+    /// Returns the family of an IP address.
+    pub fn of(address: &IpAddr) -> Self {
+        match address {
+            IpAddr::V4(_) => IpFamily::V4,
+            IpAddr::V6(_) => IpFamily::V6,
+        }
+    }
+
+    /// Returns the wildcard ("any") address of this family.
+    pub fn unspecified(self) -> IpAddr {
+        match self {
+            IpFamily::V4 => IpAddr::from([0u8; 4]),
+            IpFamily::V6 => IpAddr::from([0u8; 16]),
+        }
+    }
+}
+
+impl std::fmt::Display for IpFamily {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IpFamily::V4 => write!(f, "IPv4"),
+            IpFamily::V6 => write!(f, "IPv6"),
         }
     }
 }
 
 /// Application type detected from the command line.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+///
+/// Serialized as the variant name (e.g. `"NodeJs"`); custom types are
+/// serialized as their name.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum AppType {
     /// Node.js application (node, npm, yarn)
     NodeJs,
@@ -41,17 +85,151 @@ pub enum AppType {
     Php,
     /// Rust application (cargo, target/)
     Rust,
+    /// Deno application
+    Deno,
+    /// Bun application
+    Bun,
+    /// Elixir or Erlang application running on the BEAM (beam.smp, mix)
+    Elixir,
+    /// PostgreSQL server (postgres)
+    PostgreSql,
+    /// MySQL or MariaDB server (mysqld, mariadbd)
+    MySql,
+    /// Redis or Valkey server
+    Redis,
+    /// MongoDB server (mongod, mongos)
+    MongoDb,
+    /// Elasticsearch node
+    Elasticsearch,
+    /// Memcached server
+    Memcached,
+    /// RabbitMQ broker
+    RabbitMq,
+    /// Kafka broker
+    Kafka,
     /// Nginx web server
     Nginx,
     /// Apache web server (httpd, apache)
     Apache,
+    /// Caddy web server
+    Caddy,
+    /// Traefik proxy
+    Traefik,
+    /// HAProxy load balancer
+    HaProxy,
+    /// Docker's userland proxy forwarding published container ports
+    DockerProxy,
+    /// Visual Studio Code and its helper processes
+    VsCode,
+    /// JetBrains IDEs (IntelliJ IDEA, PyCharm, ...) and their helpers
+    JetBrains,
+    /// Application type defined in the user's detection rules
+    Custom(String),
     /// Unknown application type
     Unknown,
 }
 
+/// Broad kind of application, used to group and filter entries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub enum AppCategory {
+    /// Language runtime running application code
+    Runtime,
+    /// Database, cache or search engine
+    Database,
+    /// Message broker
+    Broker,
+    /// Web server, reverse proxy or load balancer
+    Proxy,
+    /// Editor or IDE helper process
+    Ide,
+    /// Operating system or container infrastructure
+    System,
+    /// Custom or unknown application type
+    Other,
+}
+
+impl AppCategory {
+    /// Returns the display name for this category.
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            AppCategory::Runtime => "runtime",
+            AppCategory::Database => "database",
+            AppCategory::Broker => "broker",
+            AppCategory::Proxy => "proxy",
+            AppCategory::Ide => "IDE",
+            AppCategory::System => "system",
+            AppCategory::Other => "other",
+        }
+    }
+}
+
+impl std::fmt::Display for AppCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.display_name())
+    }
+}
+
+/// Built-in application types with their identifiers and display names.
+const BUILTIN_APP_TYPES: &[(AppType, &str)] = &[
+    (AppType::NodeJs, "NodeJs"),
+    (AppType::Python, "Python"),
+    (AppType::DotNet, "DotNet"),
+    (AppType::Java, "Java"),
+    (AppType::Go, "Go"),
+    (AppType::Ruby, "Ruby"),
+    (AppType::Php, "Php"),
+    (AppType::Rust, "Rust"),
+    (AppType::Deno, "Deno"),
+    (AppType::Bun, "Bun"),
+    (AppType::Elixir, "Elixir"),
+    (AppType::PostgreSql, "PostgreSql"),
+    (AppType::MySql, "MySql"),
+    (AppType::Redis, "Redis"),
+    (AppType::MongoDb, "MongoDb"),
+    (AppType::Elasticsearch, "Elasticsearch"),
+    (AppType::Memcached, "Memcached"),
+    (AppType::RabbitMq, "RabbitMq"),
+    (AppType::Kafka, "Kafka"),
+    (AppType::Nginx, "Nginx"),
+    (AppType::Apache, "Apache"),
+    (AppType::Caddy, "Caddy"),
+    (AppType::Traefik, "Traefik"),
+    (AppType::HaProxy, "HaProxy"),
+    (AppType::DockerProxy, "DockerProxy"),
+    (AppType::VsCode, "VsCode"),
+    (AppType::JetBrains, "JetBrains"),
+    (AppType::Unknown, "Unknown"),
+];
+
 impl AppType {
+    /// Returns the application type with the given name.
+    ///
+    /// Built-in types are matched case-insensitively by identifier
+    /// (`NodeJs`) or display name (`Node.js`); any other name becomes a
+    /// [`AppType::Custom`] type.
+    pub fn from_name(name: &str) -> Self {
+        BUILTIN_APP_TYPES
+            .iter()
+            .find(|(app_type, id)| {
+                id.eq_ignore_ascii_case(name) || app_type.display_name().eq_ignore_ascii_case(name)
+            })
+            .map(|(app_type, _)| app_type.clone())
+            .unwrap_or_else(|| AppType::Custom(name.to_string()))
+    }
+
+    /// Returns the identifier used in JSON output and rules files.
+    pub fn id(&self) -> &str {
+        match self {
+            AppType::Custom(name) => name,
+            _ => BUILTIN_APP_TYPES
+                .iter()
+                .find(|(app_type, _)| app_type == self)
+                .map_or("Unknown", |(_, id)| id),
+        }
+    }
+
     /// Returns the display name for this application type.
-    pub fn display_name(&self) -> &'static str {
+    pub fn display_name(&self) -> &str {
         match self {
             AppType::NodeJs => "Node.js",
             AppType::Python => "Python",
@@ -61,11 +239,67 @@ impl AppType {
             AppType::Ruby => "Ruby",
             AppType::Php => "PHP",
             AppType::Rust => "Rust",
+            AppType::Deno => "Deno",
+            AppType::Bun => "Bun",
+            AppType::Elixir => "Elixir",
+            AppType::PostgreSql => "PostgreSQL",
+            AppType::MySql => "MySQL",
+            AppType::Redis => "Redis",
+            AppType::MongoDb => "MongoDB",
+            AppType::Elasticsearch => "Elasticsearch",
+            AppType::Memcached => "Memcached",
+            AppType::RabbitMq => "RabbitMQ",
+            AppType::Kafka => "Kafka",
             AppType::Nginx => "Nginx",
             AppType::Apache => "Apache",
+            AppType::Caddy => "Caddy",
+            AppType::Traefik => "Traefik",
+            AppType::HaProxy => "HAProxy",
+            AppType::DockerProxy => "Docker proxy",
+            AppType::VsCode => "VS Code",
+            AppType::JetBrains => "JetBrains",
+            AppType::Custom(name) => name,
             AppType::Unknown => "Unknown",
         }
     }
+
+    /// Returns the category of this application type.
+    pub fn category(&self) -> AppCategory {
+        match self {
+            AppType::NodeJs
+            | AppType::Python
+            | AppType::DotNet
+            | AppType::Java
+            | AppType::Go
+            | AppType::Ruby
+            | AppType::Php
+            | AppType::Rust
+            | AppType::Deno
+            | AppType::Bun
+            | AppType::Elixir => AppCategory::Runtime,
+            AppType::PostgreSql
+            | AppType::MySql
+            | AppType::Redis
+            | AppType::MongoDb
+            | AppType::Elasticsearch
+            | AppType::Memcached => AppCategory::Database,
+            AppType::RabbitMq | AppType::Kafka => AppCategory::Broker,
+            AppType::Nginx
+            | AppType::Apache
+            | AppType::Caddy
+            | AppType::Traefik
+            | AppType::HaProxy => AppCategory::Proxy,
+            AppType::VsCode | AppType::JetBrains => AppCategory::Ide,
+            AppType::DockerProxy => AppCategory::System,
+            AppType::Custom(_) | AppType::Unknown => AppCategory::Other,
+        }
+    }
+}
+
+impl Serialize for AppType {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.id())
+    }
 }
 
 impl std::fmt::Display for AppType {
@@ -74,6 +308,61 @@ impl std::fmt::Display for AppType {
     }
 }
 
+/// How certain an application type detection is.
+///
+/// Ordered from least to most certain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all(deserialize = "lowercase"))]
+pub enum Confidence {
+    /// A loose hint, such as a word in the executable path
+    Low,
+    /// A name or argument typical for the application
+    Medium,
+    /// An exact program name or subcommand
+    High,
+}
+
+impl std::fmt::Display for Confidence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Confidence::Low => write!(f, "low"),
+            Confidence::Medium => write!(f, "medium"),
+            Confidence::High => write!(f, "high"),
+        }
+    }
+}
+
+/// The result of application type detection.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Detection {
+    /// Detected application type (serialized as `PortEntry::app_type`)
+    #[serde(skip)]
+    pub app_type: AppType,
+    /// Detected framework (serialized as `PortEntry::framework`)
+    #[serde(skip)]
+    pub framework: Option<String>,
+    /// How certain the detection is
+    pub confidence: Confidence,
+    /// Name of the detection rule that matched (`None` if no rule matched)
+    pub matched_rule: Option<String>,
+    /// Package manifest that confirmed or corrected the detection
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manifest: Option<String>,
+}
+
+impl Detection {
+    /// Returns the detection result used when no rule matches.
+    pub fn unknown() -> Self {
+        Self {
+            app_type: AppType::Unknown,
+            framework: None,
+            confidence: Confidence::Low,
+            matched_rule: None,
+            manifest: None,
+        }
+    }
+}
+
 /// Information about a process using a network port.
 #[derive(Debug, Clone, Serialize)]
 pub struct ProcessInfo {
@@ -84,15 +373,39 @@ pub struct ProcessInfo {
     /// Full command line (if available)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub command: Option<String>,
+    /// Command line arguments, including the program as `argv[0]`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub argv: Vec<String>,
+    /// Path of the running executable (if available)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exe: Option<PathBuf>,
     /// Process start time (if available)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub started_at: Option<DateTime<Utc>>,
     /// Time elapsed since process started
-    #[serde(skip_serializing_if = "Option::is_none", serialize_with = "serialize_duration")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_duration"
+    )]
     pub elapsed: Option<Duration>,
     /// User running the process (if available)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub user: Option<String>,
+    /// Numeric user ID owning the socket or process (if available)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uid: Option<u32>,
+    /// Parent process ID (if available)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ppid: Option<u32>,
+    /// Current working directory (if available)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<PathBuf>,
+    /// Name of the project containing the working directory
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_name: Option<String>,
+    /// Root directory of the project containing the working directory
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_dir: Option<PathBuf>,
 }
 
 impl ProcessInfo {
@@ -102,19 +415,28 @@ impl ProcessInfo {
             pid,
             name: name.into(),
             command: None,
+            argv: Vec::new(),
+            exe: None,
             started_at: None,
             elapsed: None,
             user: None,
+            uid: None,
+            ppid: None,
+            cwd: None,
+            project_name: None,
+            project_dir: None,
         }
     }
 
     /// Sets the command line.
+    #[allow(dead_code)]
     pub fn with_command(mut self, command: impl Into<String>) -> Self {
         self.command = Some(command.into());
         self
     }
 
     /// Sets the start time and calculates elapsed duration.
+    #[allow(dead_code)]
     pub fn with_started_at(mut self, started_at: DateTime<Utc>) -> Self {
         self.started_at = Some(started_at);
         let now = Utc::now();
@@ -128,6 +450,7 @@ impl ProcessInfo {
     }
 
     /// Sets the user.
+    #[allow(dead_code)]
     pub fn with_user(mut self, user: impl Into<String>) -> Self {
         self.user = Some(user.into());
         self
@@ -139,18 +462,57 @@ impl ProcessInfo {
     }
 }
 
+/// Accept queue state of a listening socket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ListenQueue {
+    /// Connections waiting to be accepted
+    pub pending: u32,
+    /// Maximum queue length (the listen backlog)
+    pub backlog: u32,
+}
+
 /// A listening port entry with associated process information.
 #[derive(Debug, Clone, Serialize)]
 pub struct PortEntry {
     /// The port number (1-65535)
     pub port: u16,
-    /// The network protocol (TCP)
+    /// The network protocol (TCP or UDP)
     pub protocol: Protocol,
+    /// Local address the socket is bound to; the unspecified address
+    /// (`0.0.0.0` or `::`) means all interfaces
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bind_address: Option<IpAddr>,
+    /// IP family of the socket
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub family: Option<IpFamily>,
     /// Information about the process using this port
     pub process: ProcessInfo,
     /// Detected application type (if identifiable)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub app_type: Option<AppType>,
+    /// Category of the application type
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<AppCategory>,
+    /// Detected framework, e.g. Next.js or Django (if identifiable)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub framework: Option<String>,
+    /// Version of the runtime the process runs on, read from the
+    /// executable path (if identifiable)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub runtime_version: Option<String>,
+    /// How the application type was detected
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detection: Option<Detection>,
+    /// Service usually found on this port, from the well-known port
+    /// registry
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected_service: Option<String>,
+    /// True if the detected application type is not the expected service
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub service_mismatch: bool,
+    /// Accept queue lengths (TCP only, reported by the netlink backend)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub listen_queue: Option<ListenQueue>,
 }
 
 impl PortEntry {
@@ -159,16 +521,64 @@ impl PortEntry {
         Self {
             port,
             protocol,
+            bind_address: None,
+            family: None,
             process,
             app_type: None,
+            category: None,
+            framework: None,
+            runtime_version: None,
+            detection: None,
+            expected_service: None,
+            service_mismatch: false,
+            listen_queue: None,
+        }
+    }
+
+    /// Sets the bind address and derives the IP family from it.
+    pub fn with_bind_address(mut self, address: IpAddr) -> Self {
+        self.bind_address = Some(address);
+        self.family = Some(IpFamily::of(&address));
+        self
+    }
+
+    /// Returns the bind address for display (`-` if unknown).
+    pub fn address_display(&self) -> String {
+        self.bind_address
+            .map(|address| address.to_string())
+            .unwrap_or_else(|| "-".to_string())
+    }
+
+    /// Returns the application type for display, followed by the runtime
+    /// version if known (e.g. `Node.js 20.11.0`).
+    pub fn app_type_display(&self) -> String {
+        let app_type = self
+            .app_type
+            .as_ref()
+            .map(|t| t.display_name())
+            .unwrap_or("Unknown");
+
+        match &self.runtime_version {
+            Some(version) => format!("{} {}", app_type, version),
+            None => app_type.to_string(),
         }
     }
 
     /// Sets the application type.
+    #[allow(dead_code)]
     pub fn with_app_type(mut self, app_type: AppType) -> Self {
+        self.category = Some(app_type.category());
         self.app_type = Some(app_type);
         self
     }
+
+    /// Sets the application type and framework from a detection result.
+    pub fn set_detection(&mut self, detection: Detection) {
+        self.app_type = Some(detection.app_type.clone());
+        self.category = Some(detection.app_type.category());
+        self.framework = detection.framework.clone();
+        self.detection = Some(detection);
+    }
 }
 
 /// Formats a Duration into a human-readable string (e.g., "2h 15m", "1d 3h").
@@ -240,10 +650,74 @@ mod tests {
         assert_eq!(format_duration(Duration::from_secs(86400 + 10800)), "1d 3h");
     }
 
+    #[test]
+    fn test_with_bind_address_sets_family() {
+        let process = ProcessInfo::new(1, "node");
+        let entry =
+            PortEntry::new(3000, Protocol::Tcp, process).with_bind_address("::1".parse().unwrap());
+
+        assert_eq!(entry.family, Some(IpFamily::V6));
+        assert_eq!(entry.address_display(), "::1");
+    }
+
+    #[test]
+    fn test_address_display() {
+        let process = ProcessInfo::new(1, "node");
+        let entry = PortEntry::new(3000, Protocol::Tcp, process);
+        assert_eq!(entry.address_display(), "-");
+
+        let entry = entry.with_bind_address(IpFamily::V4.unspecified());
+        assert_eq!(entry.address_display(), "0.0.0.0");
+    }
+
     #[test]
     fn test_app_type_display() {
         assert_eq!(AppType::NodeJs.display_name(), "Node.js");
         assert_eq!(AppType::DotNet.display_name(), ".NET");
         assert_eq!(AppType::Unknown.display_name(), "Unknown");
+        assert_eq!(AppType::PostgreSql.display_name(), "PostgreSQL");
+        assert_eq!(AppType::Custom("Gleam".into()).display_name(), "Gleam");
+    }
+
+    #[test]
+    fn test_builtin_app_types_are_consistent() {
+        for (app_type, id) in BUILTIN_APP_TYPES {
+            assert_eq!(app_type.id(), *id);
+            assert_eq!(&AppType::from_name(id), app_type);
+            assert_eq!(&AppType::from_name(app_type.display_name()), app_type);
+        }
+    }
+
+    #[test]
+    fn test_app_type_category() {
+        assert_eq!(AppType::Deno.category(), AppCategory::Runtime);
+        assert_eq!(AppType::Redis.category(), AppCategory::Database);
+        assert_eq!(AppType::Kafka.category(), AppCategory::Broker);
+        assert_eq!(AppType::Traefik.category(), AppCategory::Proxy);
+        assert_eq!(AppType::JetBrains.category(), AppCategory::Ide);
+        assert_eq!(AppType::DockerProxy.category(), AppCategory::System);
+        assert_eq!(
+            AppType::Custom("Gleam".into()).category(),
+            AppCategory::Other
+        );
+    }
+
+    #[test]
+    fn test_app_type_from_name() {
+        assert_eq!(AppType::from_name("NodeJs"), AppType::NodeJs);
+        assert_eq!(AppType::from_name("node.js"), AppType::NodeJs);
+        assert_eq!(AppType::from_name(".NET"), AppType::DotNet);
+        assert_eq!(AppType::from_name("postgresql"), AppType::PostgreSql);
+        assert_eq!(
+            AppType::from_name("Gleam"),
+            AppType::Custom("Gleam".to_string())
+        );
+    }
+
+    #[test]
+    fn test_app_type_serialization() {
+        let json =
+            serde_json::to_string(&[AppType::NodeJs, AppType::Custom("Gleam".into())]).unwrap();
+        assert_eq!(json, r#"["NodeJs","Gleam"]"#);
     }
 }
@@ -0,0 +1,457 @@
+//! Columns of the port table.
+//!
+//! Every column the table can show is registered in [`COLUMNS`] with its
+//! header, alignment and a function extracting its value from an entry.
+//! Showing a new field of [`PortEntry`] only takes a new entry there.
+//! Column widths are computed from the values being printed, in terminal
+//! columns, so wide (e.g. CJK) characters line up.
+
+use super::service_display;
+use crate::models::PortEntry;
+use chrono::Local;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Maximum width of a truncated value, including the `...`.
+const MAX_VALUE_LENGTH: usize = 60;
+
+/// Separator between columns.
+const SEPARATOR: &str = "  ";
+
+/// Columns shown by default.
+pub const DEFAULT_COLUMNS: &[&str] = &[
+    "port",
+    "proto",
+    "address",
+    "pid",
+    "type",
+    "framework",
+    "project",
+    "service",
+    "uptime",
+    "command",
+];
+
+/// Columns shown with `-o wide`.
+pub const WIDE_COLUMNS: &[&str] = &[
+    "port",
+    "proto",
+    "address",
+    "pid",
+    "ppid",
+    "user",
+    "type",
+    "framework",
+    "project",
+    "service",
+    "started",
+    "uptime",
+    "command",
+];
+
+/// Alignment of the values in a column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Right,
+}
+
+/// A column of the port table.
+#[derive(Debug)]
+pub struct Column {
+    /// Name used with `--columns`
+    pub name: &'static str,
+    /// Header printed above the column
+    pub header: &'static str,
+    /// Whether values are padded on the right or on the left
+    pub align: Align,
+    /// Minimum width, so that short columns line up across refreshes
+    pub min_width: usize,
+    /// Whether long values are truncated unless `-o wide` is given
+    pub truncate: bool,
+    /// Returns the value of the column for an entry
+    pub value: fn(&PortEntry) -> String,
+}
+
+/// Every column the table can show.
+pub const COLUMNS: &[Column] = &[
+    Column {
+        name: "port",
+        header: "PORT",
+        align: Align::Right,
+        min_width: 5,
+        truncate: false,
+        value: |e| e.port.to_string(),
+    },
+    Column {
+        name: "proto",
+        header: "PROTO",
+        align: Align::Left,
+        min_width: 0,
+        truncate: false,
+        value: |e| e.protocol.to_string(),
+    },
+    Column {
+        name: "address",
+        header: "ADDRESS",
+        align: Align::Left,
+        min_width: 0,
+        truncate: false,
+        value: |e| e.address_display(),
+    },
+    Column {
+        name: "family",
+        header: "FAMILY",
+        align: Align::Left,
+        min_width: 0,
+        truncate: false,
+        value: |e| or_dash(e.family.map(|family| family.to_string())),
+    },
+    Column {
+        name: "pid",
+        header: "PID",
+        align: Align::Right,
+        min_width: 6,
+        truncate: false,
+        value: |e| e.process.pid.to_string(),
+    },
+    Column {
+        name: "ppid",
+        header: "PPID",
+        align: Align::Right,
+        min_width: 6,
+        truncate: false,
+        value: |e| or_dash(e.process.ppid.map(|ppid| ppid.to_string())),
+    },
+    Column {
+        name: "name",
+        header: "NAME",
+        align: Align::Left,
+        min_width: 0,
+        truncate: false,
+        value: |e| e.process.name.clone(),
+    },
+    Column {
+        name: "user",
+        header: "USER",
+        align: Align::Left,
+        min_width: 0,
+        truncate: false,
+        value: |e| {
+            or_dash(
+                e.process
+                    .user
+                    .clone()
+                    .or_else(|| e.process.uid.map(|uid| uid.to_string())),
+            )
+        },
+    },
+    Column {
+        name: "type",
+        header: "TYPE",
+        align: Align::Left,
+        min_width: 8,
+        truncate: false,
+        value: |e| e.app_type_display(),
+    },
+    Column {
+        name: "category",
+        header: "CATEGORY",
+        align: Align::Left,
+        min_width: 0,
+        truncate: false,
+        value: |e| or_dash(e.category.map(|category| category.to_string())),
+    },
+    Column {
+        name: "framework",
+        header: "FRAMEWORK",
+        align: Align::Left,
+        min_width: 0,
+        truncate: false,
+        value: |e| or_dash(e.framework.clone()),
+    },
+    Column {
+        name: "project",
+        header: "PROJECT",
+        align: Align::Left,
+        min_width: 0,
+        truncate: false,
+        value: |e| or_dash(e.process.project_name.clone()),
+    },
+    Column {
+        name: "service",
+        header: "SERVICE",
+        align: Align::Left,
+        min_width: 0,
+        truncate: false,
+        value: service_display,
+    },
+    Column {
+        name: "started",
+        header: "STARTED",
+        align: Align::Left,
+        min_width: 0,
+        truncate: false,
+        value: |e| {
+            or_dash(e.process.started_at.map(|started| {
+                started
+                    .with_timezone(&Local)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string()
+            }))
+        },
+    },
+    Column {
+        name: "uptime",
+        header: "UPTIME",
+        align: Align::Right,
+        min_width: 8,
+        truncate: false,
+        value: |e| or_dash(e.process.elapsed_human()),
+    },
+    Column {
+        name: "cwd",
+        header: "CWD",
+        align: Align::Left,
+        min_width: 0,
+        truncate: true,
+        value: |e| or_dash(e.process.cwd.as_ref().map(|cwd| cwd.display().to_string())),
+    },
+    Column {
+        name: "command",
+        header: "COMMAND",
+        align: Align::Left,
+        min_width: 0,
+        truncate: true,
+        value: |e| {
+            e.process
+                .command
+                .clone()
+                .unwrap_or_else(|| e.process.name.clone())
+        },
+    },
+];
+
+/// Returns the column with the given name.
+pub fn column(name: &str) -> Option<&'static Column> {
+    COLUMNS
+        .iter()
+        .find(|column| column.name.eq_ignore_ascii_case(name))
+}
+
+/// Parses a column name given with `--columns`.
+pub fn parse_column(name: &str) -> Result<&'static Column, String> {
+    column(name.trim()).ok_or_else(|| {
+        let names: Vec<&str> = COLUMNS.iter().map(|c| c.name).collect();
+        format!(
+            "unknown column '{}' (expected one of {})",
+            name,
+            names.join(", ")
+        )
+    })
+}
+
+/// The columns of the table and how they are printed.
+#[derive(Debug, Clone)]
+pub struct TableLayout {
+    /// Columns, in display order
+    pub columns: Vec<&'static Column>,
+    /// Print long values in full
+    pub wide: bool,
+    /// Print the header line
+    pub header: bool,
+}
+
+impl TableLayout {
+    /// Returns a layout of the named columns.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a name is not registered in [`COLUMNS`].
+    pub fn with_columns(names: &[&str]) -> Self {
+        Self {
+            columns: names
+                .iter()
+                .map(|name| column(name).expect("unregistered column"))
+                .collect(),
+            wide: false,
+            header: true,
+        }
+    }
+
+    /// Returns the layout of `-o wide`.
+    pub fn wide() -> Self {
+        Self {
+            wide: true,
+            ..Self::with_columns(WIDE_COLUMNS)
+        }
+    }
+
+    /// Formats entries as table lines, the header first (even if it is not
+    /// to be printed) and then one line per entry.
+    pub fn lines(&self, entries: &[PortEntry]) -> Vec<String> {
+        let cells: Vec<Vec<String>> = entries
+            .iter()
+            .map(|entry| {
+                self.columns
+                    .iter()
+                    .map(|column| {
+                        let value = (column.value)(entry);
+                        if column.truncate && !self.wide {
+                            truncate(&value, MAX_VALUE_LENGTH)
+                        } else {
+                            value
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let widths: Vec<usize> = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(i, column)| {
+                cells
+                    .iter()
+                    .map(|row| row[i].width())
+                    .max()
+                    .unwrap_or(0)
+                    .max(column.header.width())
+                    .max(column.min_width)
+            })
+            .collect();
+
+        let header: Vec<String> = self.columns.iter().map(|c| c.header.to_string()).collect();
+        std::iter::once(header)
+            .chain(cells)
+            .map(|row| self.format_row(&row, &widths))
+            .collect()
+    }
+
+    /// Joins the cells of a row, padding each to the width of its column.
+    /// The last column is not padded if it is left-aligned.
+    fn format_row(&self, row: &[String], widths: &[usize]) -> String {
+        let last = self.columns.len().saturating_sub(1);
+        let cells: Vec<String> = self
+            .columns
+            .iter()
+            .zip(row.iter().zip(widths))
+            .enumerate()
+            .map(|(i, (column, (value, &width)))| {
+                // format! pads by characters, not by terminal columns
+                let padding = " ".repeat(width.saturating_sub(value.width()));
+                match column.align {
+                    Align::Left if i == last => value.clone(),
+                    Align::Left => format!("{}{}", value, padding),
+                    Align::Right => format!("{}{}", padding, value),
+                }
+            })
+            .collect();
+        cells.join(SEPARATOR)
+    }
+}
+
+impl Default for TableLayout {
+    fn default() -> Self {
+        Self::with_columns(DEFAULT_COLUMNS)
+    }
+}
+
+fn or_dash(value: Option<String>) -> String {
+    value.unwrap_or_else(|| "-".to_string())
+}
+
+/// Shortens a value to `max` terminal columns, ending it with `...`.
+fn truncate(value: &str, max: usize) -> String {
+    if value.width() <= max {
+        return value.to_string();
+    }
+
+    let mut width = 0;
+    let kept: String = value
+        .chars()
+        .take_while(|c| {
+            width += c.width().unwrap_or(0);
+            width <= max - 3
+        })
+        .collect();
+    format!("{}...", kept)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{AppType, ProcessInfo, Protocol};
+
+    fn entries() -> Vec<PortEntry> {
+        let mut node = ProcessInfo::new(4242, "node")
+            .with_command(format!("node {}", "a".repeat(80)))
+            .with_user("alice");
+        node.ppid = Some(1);
+        vec![
+            PortEntry::new(3000, Protocol::Tcp, node)
+                .with_bind_address("::1".parse().unwrap())
+                .with_app_type(AppType::NodeJs),
+            PortEntry::new(5353, Protocol::Udp, ProcessInfo::new(1234567, "mdns")),
+        ]
+    }
+
+    #[test]
+    fn test_registry() {
+        for name in DEFAULT_COLUMNS.iter().chain(WIDE_COLUMNS) {
+            assert!(column(name).is_some(), "{}", name);
+        }
+        assert_eq!(parse_column("PID").unwrap().header, "PID");
+        assert!(parse_column("size")
+            .unwrap_err()
+            .contains("expected one of port, proto"));
+    }
+
+    #[test]
+    fn test_lines() {
+        let layout = TableLayout::with_columns(&["port", "pid", "user", "type", "address"]);
+        let lines = layout.lines(&entries());
+
+        assert_eq!(
+            lines,
+            vec![
+                " PORT      PID  USER   TYPE      ADDRESS",
+                " 3000     4242  alice  Node.js   ::1",
+                " 5353  1234567  -      Unknown   -",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_truncate_unless_wide() {
+        let layout = TableLayout::with_columns(&["pid", "command"]);
+        let lines = layout.lines(&entries());
+        assert!(lines[1].ends_with("aaa..."));
+        assert_eq!(lines[1].chars().count(), 7 + 2 + MAX_VALUE_LENGTH);
+
+        let lines = TableLayout::wide().lines(&entries());
+        assert!(lines[0].contains("PPID"));
+        assert!(lines[1].ends_with(&"a".repeat(80)));
+
+        // Wide characters take two columns each
+        assert_eq!(truncate("日本語のコマンド", 9), "日本語...");
+        assert_eq!(truncate("日本語のコマンド", 10), "日本語...");
+    }
+
+    #[test]
+    fn test_lines_align_wide_characters() {
+        let mut entries = entries();
+        entries[0].process.user = Some("山田".to_string());
+        entries[0].process.project_name = Some("ウェブ".to_string());
+        let layout = TableLayout::with_columns(&["user", "project", "pid"]);
+
+        assert_eq!(
+            layout.lines(&entries),
+            vec![
+                "USER  PROJECT      PID",
+                "山田  ウェブ      4242",
+                "-     -        1234567",
+            ]
+        );
+    }
+}
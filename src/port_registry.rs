@@ -0,0 +1,313 @@
+//! Registry of well-known ports.
+//!
+//! Even when the process holding a port cannot be identified, the port
+//! number says what is usually found there (5432 → PostgreSQL, 9229 → the
+//! Node.js inspector). The built-in registry is embedded from
+//! `well_known_ports.toml`; users can add or override entries in
+//! `~/.config/ports/ports.toml`:
+//!
+//! ```toml
+//! [[port]]
+//! port = 8081
+//! service = "Metro bundler"
+//! app_types = ["NodeJs"]
+//! ```
+//!
+//! `app_types` names built-in types, or custom types detected by rules in
+//! `rules.toml`.
+
+use crate::app_detector::{self, RuleSet};
+use crate::config;
+use crate::error::{PortsError, Result};
+use crate::models::{AppType, PortEntry, Protocol};
+use serde::Deserialize;
+use std::sync::OnceLock;
+
+/// Built-in port registry.
+const WELL_KNOWN_PORTS: &str = include_str!("port_registry/well_known_ports.toml");
+
+/// Name of the user port registry file in the configuration directory.
+pub const PORTS_FILE_NAME: &str = "ports.toml";
+
+/// Contents of a port registry file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PortsFile {
+    #[serde(default, rename = "port")]
+    ports: Vec<KnownPortSpec>,
+}
+
+/// A registry entry as written in a port registry file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct KnownPortSpec {
+    port: u16,
+    protocol: Option<String>,
+    service: String,
+    #[serde(default)]
+    app_types: Vec<String>,
+}
+
+/// The service usually found on a port.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KnownPort {
+    /// Port number
+    pub port: u16,
+    /// Protocol the entry applies to (`None` for both)
+    pub protocol: Option<Protocol>,
+    /// Name of the service
+    pub service: String,
+    /// Application types expected to hold the port (empty if any type is
+    /// plausible)
+    pub app_types: Vec<AppType>,
+}
+
+impl KnownPort {
+    /// Converts a registry file entry, rejecting application types the
+    /// rules cannot detect (usually misspelled ones).
+    fn from_spec(spec: KnownPortSpec, rules: &RuleSet) -> std::result::Result<Self, String> {
+        let protocol = match spec.protocol.as_deref().map(str::to_lowercase).as_deref() {
+            None => None,
+            Some("tcp") => Some(Protocol::Tcp),
+            Some("udp") => Some(Protocol::Udp),
+            Some(other) => {
+                return Err(format!(
+                    "port {}: unknown protocol '{}' (expected tcp or udp)",
+                    spec.port, other
+                ))
+            }
+        };
+
+        let mut app_types = Vec::with_capacity(spec.app_types.len());
+        for name in &spec.app_types {
+            let app_type = AppType::from_name(name);
+            if matches!(app_type, AppType::Custom(_)) && !rules.detects(&app_type) {
+                return Err(format!(
+                    "port {}: unknown app type '{}' (expected a built-in type such as \
+                     PostgreSql, or one detected by a rule in {})",
+                    spec.port,
+                    name,
+                    app_detector::RULES_FILE_NAME
+                ));
+            }
+            app_types.push(app_type);
+        }
+
+        Ok(Self {
+            port: spec.port,
+            protocol,
+            service: spec.service,
+            app_types,
+        })
+    }
+
+    /// Returns true if the entry applies to a socket.
+    fn applies_to(&self, port: u16, protocol: Protocol) -> bool {
+        self.port == port && self.protocol.is_none_or(|p| p == protocol)
+    }
+
+    /// Returns true if `app_type` is unexpected on this port.
+    ///
+    /// Unknown types are never a mismatch, and neither is Docker's proxy,
+    /// which publishes the ports of containers (e.g. a PostgreSQL container
+    /// on 5432).
+    pub fn is_mismatch(&self, app_type: &AppType) -> bool {
+        !self.app_types.is_empty()
+            && !matches!(app_type, AppType::Unknown | AppType::DockerProxy)
+            && !self.app_types.contains(app_type)
+    }
+}
+
+/// A table of well-known ports.
+#[derive(Debug, Clone)]
+pub struct PortRegistry {
+    ports: Vec<KnownPort>,
+}
+
+impl PortRegistry {
+    /// Returns the built-in registry.
+    pub fn builtin() -> &'static PortRegistry {
+        static BUILTIN: OnceLock<PortRegistry> = OnceLock::new();
+        BUILTIN.get_or_init(|| {
+            let mut registry = PortRegistry { ports: Vec::new() };
+            parse_ports_file(WELL_KNOWN_PORTS)
+                .and_then(|file| registry.merge(file, RuleSet::builtin()))
+                .expect("built-in port registry is valid");
+            registry
+        })
+    }
+
+    /// Returns the built-in registry combined with the user's registry
+    /// file, whose application types must be detectable by `rules`.
+    pub fn load(rules: &RuleSet) -> Result<PortRegistry> {
+        let mut registry = Self::builtin().clone();
+
+        if let Some((path, content)) = config::read_config_file(PORTS_FILE_NAME)? {
+            parse_ports_file(&content)
+                .and_then(|file| registry.merge(file, rules))
+                .map_err(|e| PortsError::config_error(format!("{}: {}", path.display(), e)))?;
+        }
+
+        Ok(registry)
+    }
+
+    /// Returns the registry entry for a socket.
+    pub fn lookup(&self, port: u16, protocol: Protocol) -> Option<&KnownPort> {
+        self.ports
+            .iter()
+            .find(|known| known.applies_to(port, protocol))
+    }
+
+    /// Sets the expected service of an entry and flags an unexpected
+    /// application type.
+    pub fn annotate(&self, entry: &mut PortEntry) {
+        let Some(known) = self.lookup(entry.port, entry.protocol) else {
+            return;
+        };
+
+        entry.expected_service = Some(known.service.clone());
+        entry.service_mismatch = entry
+            .app_type
+            .as_ref()
+            .is_some_and(|app_type| known.is_mismatch(app_type));
+    }
+
+    /// Adds the entries of a registry file in front of the existing ones,
+    /// so that they take precedence.
+    fn merge(&mut self, file: PortsFile, rules: &RuleSet) -> std::result::Result<(), String> {
+        let mut added = file
+            .ports
+            .into_iter()
+            .map(|spec| KnownPort::from_spec(spec, rules))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        added.append(&mut self.ports);
+        self.ports = added;
+        Ok(())
+    }
+}
+
+/// Parses a port registry file.
+fn parse_ports_file(content: &str) -> std::result::Result<PortsFile, String> {
+    toml::from_str(content).map_err(|e| e.message().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ProcessInfo;
+
+    fn registry(content: &str) -> std::result::Result<PortRegistry, String> {
+        let mut registry = PortRegistry::builtin().clone();
+        registry.merge(parse_ports_file(content)?, RuleSet::builtin())?;
+        Ok(registry)
+    }
+
+    fn entry(port: u16, protocol: Protocol, app_type: AppType) -> PortEntry {
+        PortEntry::new(port, protocol, ProcessInfo::new(1, "test")).with_app_type(app_type)
+    }
+
+    #[test]
+    fn test_builtin_registry_is_valid() {
+        let ports = &PortRegistry::builtin().ports;
+        assert!(!ports.is_empty());
+
+        for known in ports {
+            assert!(
+                known
+                    .app_types
+                    .iter()
+                    .all(|t| !matches!(t, AppType::Custom(_))),
+                "port {} lists an unknown app type",
+                known.port
+            );
+        }
+    }
+
+    #[test]
+    fn test_lookup() {
+        let registry = PortRegistry::builtin();
+
+        assert_eq!(
+            registry
+                .lookup(5432, Protocol::Tcp)
+                .map(|k| k.service.as_str()),
+            Some("PostgreSQL")
+        );
+        assert_eq!(
+            registry
+                .lookup(5353, Protocol::Udp)
+                .map(|k| k.service.as_str()),
+            Some("mDNS")
+        );
+        assert_eq!(registry.lookup(5353, Protocol::Tcp), None);
+        assert_eq!(registry.lookup(41234, Protocol::Tcp), None);
+    }
+
+    #[test]
+    fn test_annotate_mismatch() {
+        let registry = PortRegistry::builtin();
+
+        let mut postgres = entry(5432, Protocol::Tcp, AppType::PostgreSql);
+        registry.annotate(&mut postgres);
+        assert_eq!(postgres.expected_service.as_deref(), Some("PostgreSQL"));
+        assert!(!postgres.service_mismatch);
+
+        let mut node = entry(5432, Protocol::Tcp, AppType::NodeJs);
+        registry.annotate(&mut node);
+        assert!(node.service_mismatch);
+
+        // Unidentified processes and published container ports
+        for app_type in [AppType::Unknown, AppType::DockerProxy] {
+            let mut other = entry(5432, Protocol::Tcp, app_type);
+            registry.annotate(&mut other);
+            assert!(!other.service_mismatch);
+        }
+
+        // Shared development ports accept any type
+        let mut dev = entry(3000, Protocol::Tcp, AppType::Ruby);
+        registry.annotate(&mut dev);
+        assert_eq!(dev.expected_service.as_deref(), Some("Dev server"));
+        assert!(!dev.service_mismatch);
+    }
+
+    #[test]
+    fn test_user_entries_take_precedence() {
+        let registry = registry(
+            r#"
+            [[port]]
+            port = 8081
+            service = "Metro bundler"
+            app_types = ["NodeJs"]
+
+            [[port]]
+            port = 5432
+            protocol = "TCP"
+            service = "Postgres (Docker)"
+            app_types = ["PostgreSql", "Go"]
+            "#,
+        )
+        .unwrap();
+
+        let known = registry.lookup(8081, Protocol::Tcp).unwrap();
+        assert_eq!(known.service, "Metro bundler");
+        assert_eq!(known.app_types, vec![AppType::NodeJs]);
+
+        let known = registry.lookup(5432, Protocol::Tcp).unwrap();
+        assert_eq!(known.service, "Postgres (Docker)");
+        assert!(!known.is_mismatch(&AppType::Go));
+    }
+
+    #[test]
+    fn test_invalid_ports_files() {
+        assert!(registry("[[port]]\nport = 70000\nservice = \"x\"\n").is_err());
+        assert!(registry("[[port]]\nport = 1\n").is_err());
+        assert!(registry("[[port]]\nport = 1\nservice = \"x\"\nname = \"y\"\n").is_err());
+        assert!(registry("[[port]]\nport = 1\nservice = \"x\"\nprotocol = \"sctp\"\n").is_err());
+        assert!(
+            registry("[[port]]\nport = 1\nservice = \"x\"\napp_types = [\"Postgres\"]\n")
+                .unwrap_err()
+                .contains("unknown app type 'Postgres'")
+        );
+    }
+}
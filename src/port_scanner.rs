@@ -1,96 +1,230 @@
 //! Port scanning functionality.
 //!
-//! This module provides functions to scan for listening TCP ports on macOS.
+//! This module provides functions to scan for listening TCP ports and
+//! bound UDP ports.
+//! Scanning is delegated to a [`ScannerBackend`]; several backends are
+//! available (lsof, Linux netlink and procfs, ss and netstat) and they are tried in
+//! a platform-specific fallback order until one of them succeeds.
+
+mod lsof;
+mod netlink;
+mod netstat;
+mod procfs;
+mod ss;
 
 use crate::error::{PortsError, Result};
-use crate::models::{PortEntry, ProcessInfo, Protocol};
+use crate::models::{IpFamily, PortEntry};
+use crate::output;
+use std::env;
+use std::net::IpAddr;
+use std::path::Path;
 use std::process::Command;
 
-/// Scans for all listening TCP ports and returns port entries.
+pub use lsof::LsofBackend;
+pub use netlink::NetlinkBackend;
+pub use netstat::NetstatBackend;
+pub use procfs::ProcfsBackend;
+pub use ss::SsBackend;
+
+/// A source of listening port information.
+pub trait ScannerBackend {
+    /// Returns the short name of this backend (as accepted by `--backend`).
+    fn name(&self) -> &'static str;
+
+    /// Returns true if this backend can run on the current system
+    /// (e.g. the required tool is installed).
+    fn is_available(&self) -> bool;
+
+    /// Scans for all listening TCP ports and bound (unconnected) UDP ports.
+    fn scan(&self) -> Result<Vec<PortEntry>>;
+}
+
+/// Backend selection for port scanning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum BackendKind {
+    /// Use the platform's preferred backend, falling back as needed
+    Auto,
+    /// `lsof -iTCP -sTCP:LISTEN` and `lsof -iUDP`
+    Lsof,
+    /// Linux NETLINK_SOCK_DIAG (inet_diag) queries
+    Netlink,
+    /// Linux `/proc/net/{tcp,udp}{,6}` tables
+    Procfs,
+    /// `ss -ltunpH` (Linux)
+    Ss,
+    /// `netstat -tulnp` (Linux) or `netstat -anv` (macOS)
+    Netstat,
+}
+
+/// Backend order tried when no backend is explicitly requested.
+#[cfg(target_os = "linux")]
+const DEFAULT_CHAIN: [BackendKind; 5] = [
+    BackendKind::Netlink,
+    BackendKind::Procfs,
+    BackendKind::Ss,
+    BackendKind::Netstat,
+    BackendKind::Lsof,
+];
+
+/// Backend order tried when no backend is explicitly requested.
+#[cfg(not(target_os = "linux"))]
+const DEFAULT_CHAIN: [BackendKind; 2] = [BackendKind::Lsof, BackendKind::Netstat];
+
+impl BackendKind {
+    /// Creates the backend implementation for this kind.
+    ///
+    /// Returns `None` for `Auto`, which is resolved by [`fallback_chain`].
+    fn backend(self) -> Option<Box<dyn ScannerBackend>> {
+        match self {
+            BackendKind::Auto => None,
+            BackendKind::Lsof => Some(Box::new(LsofBackend)),
+            BackendKind::Netlink => Some(Box::new(NetlinkBackend)),
+            BackendKind::Procfs => Some(Box::new(ProcfsBackend)),
+            BackendKind::Ss => Some(Box::new(SsBackend)),
+            BackendKind::Netstat => Some(Box::new(NetstatBackend)),
+        }
+    }
+}
+
+/// Returns the order in which backends are tried.
+///
+/// The preferred backend comes first, followed by the remaining backends
+/// of the platform's default chain.
+fn fallback_chain(preferred: BackendKind) -> Vec<BackendKind> {
+    let mut chain = Vec::new();
+    if preferred != BackendKind::Auto {
+        chain.push(preferred);
+    }
+    chain.extend(
+        DEFAULT_CHAIN
+            .iter()
+            .copied()
+            .filter(|kind| *kind != preferred),
+    );
+    chain
+}
+
+/// Scans for all listening TCP and bound UDP ports and returns port entries.
 ///
-/// Uses the `lsof` command to find listening TCP sockets and their
-/// associated processes. This approach is reliable on macOS and
-/// works with standard user permissions.
-pub fn scan_listening_ports() -> Result<Vec<PortEntry>> {
-    // Use lsof to get listening TCP ports
-    // -iTCP: Select TCP connections
-    // -sTCP:LISTEN: Only show LISTEN state
-    // -n: Don't resolve hostnames (faster)
-    // -P: Don't resolve port names (show numbers)
-    let output = Command::new("lsof")
-        .args(["-iTCP", "-sTCP:LISTEN", "-n", "-P", "-F", "pcn"])
+/// Backends are tried in fallback order, starting with `preferred`.
+/// Backends that are unavailable (missing tool, no /proc) are skipped, and
+/// a backend that fails hands over to the next one. If every backend fails,
+/// the first error encountered is returned. Falling back from a backend
+/// requested with `--backend` is reported as a warning, so the results are
+/// never silently taken from another backend.
+pub fn scan_listening_ports(preferred: BackendKind) -> Result<Vec<PortEntry>> {
+    let mut first_error = None;
+    let mut tried = Vec::new();
+
+    for backend in fallback_chain(preferred)
+        .into_iter()
+        .filter_map(BackendKind::backend)
+    {
+        tried.push(backend.name());
+        let requested = preferred != BackendKind::Auto && tried.len() == 1;
+        if !backend.is_available() {
+            if requested {
+                output::print_warning(&format!(
+                    "The {} backend is not available on this system; trying the others",
+                    backend.name()
+                ));
+            }
+            continue;
+        }
+
+        match backend.scan() {
+            Ok(entries) => return Ok(entries),
+            Err(e) => {
+                if requested {
+                    output::print_warning(&format!(
+                        "The {} backend failed: {}; trying the others",
+                        backend.name(),
+                        e
+                    ));
+                }
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+
+    Err(first_error.unwrap_or_else(|| {
+        PortsError::system_error(format!(
+            "No port scanning backend is available (tried: {})",
+            tried.join(", ")
+        ))
+    }))
+}
+
+/// Returns true if an executable named `program` is found in `PATH`.
+fn command_exists(program: &str) -> bool {
+    let Some(paths) = env::var_os("PATH") else {
+        return false;
+    };
+
+    env::split_paths(&paths).any(|dir| is_executable(&dir.join(program)))
+}
+
+/// Returns true if `path` is an executable regular file.
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    path.metadata()
+        .map(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+/// Runs an external command and returns its standard output.
+fn run_command(program: &str, args: &[&str]) -> Result<String> {
+    let output = Command::new(program)
+        .args(args)
         .output()
-        .map_err(|e| PortsError::system_error(format!("Failed to execute lsof: {}", e)))?;
+        .map_err(|e| PortsError::system_error(format!("Failed to execute {}: {}", program, e)))?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
         return Err(PortsError::system_error(format!(
-            "lsof failed: {}",
+            "{} failed: {}",
+            program,
             stderr.trim()
         )));
     }
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    parse_lsof_output(&stdout)
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
 }
 
-/// Parses lsof -F output format.
+/// Parses the host part of a socket address as printed by the scanning tools.
 ///
-/// Format uses single-letter prefixes:
-/// - p: PID
-/// - c: Command name
-/// - n: Name (includes port info like *:3000 or 127.0.0.1:8080)
-fn parse_lsof_output(output: &str) -> Result<Vec<PortEntry>> {
-    let mut entries = Vec::new();
-    let mut current_pid: Option<u32> = None;
-    let mut current_name: Option<String> = None;
-
-    for line in output.lines() {
-        if line.is_empty() {
-            continue;
-        }
-
-        let prefix = line.chars().next().unwrap_or(' ');
-        let value = &line[1..];
-
-        match prefix {
-            'p' => {
-                current_pid = value.parse().ok();
-            }
-            'c' => {
-                current_name = Some(value.to_string());
-            }
-            'n' => {
-                // Parse port from name like "*:3000" or "127.0.0.1:8080"
-                if let Some(port) = extract_port_from_name(value) {
-                    if let (Some(pid), Some(name)) = (current_pid, current_name.as_ref()) {
-                        let process = ProcessInfo::new(pid, name.clone());
-                        let entry = PortEntry::new(port, Protocol::Tcp, process);
-
-                        // Avoid duplicates (same port, same PID)
-                        if !entries.iter().any(|e: &PortEntry| e.port == port && e.process.pid == pid) {
-                            entries.push(entry);
-                        }
-                    }
-                }
-            }
-            _ => {}
-        }
+/// Accepts bracketed IPv6 (`[::1]`), zone suffixes (`fe80::1%lo0`,
+/// `127.0.0.53%lo`) and the `*` wildcard, which is mapped to the
+/// unspecified address of `wildcard_family`.
+fn parse_host(host: &str, wildcard_family: IpFamily) -> Option<IpAddr> {
+    if host == "*" {
+        return Some(wildcard_family.unspecified());
     }
 
-    Ok(entries)
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    let host = host
+        .split_once('%')
+        .map_or(host, |(address, _zone)| address);
+    host.parse().ok()
 }
 
-/// Extracts the port number from an lsof name field.
+/// Adds an entry unless one with the same port, protocol, bind address and
+/// PID already exists.
 ///
-/// Examples:
-/// - "*:3000" -> Some(3000)
-/// - "127.0.0.1:8080" -> Some(8080)
-/// - "[::1]:9000" -> Some(9000)
-fn extract_port_from_name(name: &str) -> Option<u16> {
-    // Find the last colon and parse the port after it
-    let port_str = name.rsplit(':').next()?;
-    port_str.parse().ok()
+/// Processes sharing a socket, or tools reporting a socket once per file
+/// descriptor, otherwise show up more than once.
+fn push_unique(entries: &mut Vec<PortEntry>, entry: PortEntry) {
+    let is_duplicate = entries.iter().any(|e| {
+        e.port == entry.port
+            && e.protocol == entry.protocol
+            && e.bind_address == entry.bind_address
+            && e.process.pid == entry.process.pid
+    });
+
+    if !is_duplicate {
+        entries.push(entry);
+    }
 }
 
 #[cfg(test)]
@@ -98,31 +232,45 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_extract_port_from_wildcard() {
-        assert_eq!(extract_port_from_name("*:3000"), Some(3000));
+    fn test_fallback_chain_auto_uses_default_order() {
+        assert_eq!(fallback_chain(BackendKind::Auto), DEFAULT_CHAIN.to_vec());
     }
 
     #[test]
-    fn test_extract_port_from_ipv4() {
-        assert_eq!(extract_port_from_name("127.0.0.1:8080"), Some(8080));
+    fn test_fallback_chain_preferred_first() {
+        let chain = fallback_chain(BackendKind::Netstat);
+
+        assert_eq!(chain[0], BackendKind::Netstat);
+        assert_eq!(
+            chain.iter().filter(|k| **k == BackendKind::Netstat).count(),
+            1
+        );
+        assert!(DEFAULT_CHAIN.iter().all(|kind| chain.contains(kind)));
     }
 
     #[test]
-    fn test_extract_port_from_ipv6() {
-        assert_eq!(extract_port_from_name("[::1]:9000"), Some(9000));
+    fn test_parse_host() {
+        assert_eq!(
+            parse_host("127.0.0.1", IpFamily::V4),
+            "127.0.0.1".parse().ok()
+        );
+        assert_eq!(parse_host("[::1]", IpFamily::V4), "::1".parse().ok());
+        assert_eq!(
+            parse_host("127.0.0.53%lo", IpFamily::V4),
+            "127.0.0.53".parse().ok()
+        );
+        assert_eq!(
+            parse_host("[fe80::1%lo0]", IpFamily::V6),
+            "fe80::1".parse().ok()
+        );
+        assert_eq!(parse_host("*", IpFamily::V4), "0.0.0.0".parse().ok());
+        assert_eq!(parse_host("*", IpFamily::V6), "::".parse().ok());
+        assert_eq!(parse_host("localhost", IpFamily::V4), None);
     }
 
     #[test]
-    fn test_parse_lsof_output() {
-        let output = "p1234\ncnode\nn*:3000\np5678\ncpython\nn127.0.0.1:8080\n";
-        let entries = parse_lsof_output(output).unwrap();
-
-        assert_eq!(entries.len(), 2);
-        assert_eq!(entries[0].port, 3000);
-        assert_eq!(entries[0].process.pid, 1234);
-        assert_eq!(entries[0].process.name, "node");
-        assert_eq!(entries[1].port, 8080);
-        assert_eq!(entries[1].process.pid, 5678);
-        assert_eq!(entries[1].process.name, "python");
+    fn test_command_exists() {
+        assert!(command_exists("sh"));
+        assert!(!command_exists("definitely-not-a-real-command-ports"));
     }
 }
@@ -0,0 +1,218 @@
+//! lsof port scanning backend.
+//!
+//! Uses the `lsof` command to find listening TCP sockets, bound UDP
+//! sockets and their associated processes. This approach is reliable on
+//! macOS and works with standard user permissions.
+
+use super::{command_exists, parse_host, push_unique, ScannerBackend};
+use crate::error::{PortsError, Result};
+use crate::models::{IpFamily, PortEntry, ProcessInfo, Protocol};
+use std::net::IpAddr;
+use std::process::Command;
+
+/// Scanner backend based on `lsof -iTCP -sTCP:LISTEN` and `lsof -iUDP`.
+pub struct LsofBackend;
+
+impl ScannerBackend for LsofBackend {
+    fn name(&self) -> &'static str {
+        "lsof"
+    }
+
+    fn is_available(&self) -> bool {
+        command_exists("lsof")
+    }
+
+    fn scan(&self) -> Result<Vec<PortEntry>> {
+        // -iTCP: Select TCP connections
+        // -sTCP:LISTEN: Only show LISTEN state
+        // -n: Don't resolve hostnames (faster)
+        // -P: Don't resolve port names (show numbers)
+        let tcp_output = run_lsof(&["-iTCP", "-sTCP:LISTEN", "-n", "-P", "-F", "pctn"])?;
+        let mut entries = parse_lsof_output(&tcp_output, Protocol::Tcp)?;
+
+        // UDP has no LISTEN state; connected sockets are filtered by the parser
+        let udp_output = run_lsof(&["-iUDP", "-n", "-P", "-F", "pctn"])?;
+        entries.extend(parse_lsof_output(&udp_output, Protocol::Udp)?);
+
+        Ok(entries)
+    }
+}
+
+/// Runs lsof and returns its standard output.
+///
+/// lsof exits with status 1 and prints nothing when no file matches the
+/// selection, which is not an error for our purposes.
+fn run_lsof(args: &[&str]) -> Result<String> {
+    let output = Command::new("lsof")
+        .args(args)
+        .output()
+        .map_err(|e| PortsError::system_error(format!("Failed to execute lsof: {}", e)))?;
+
+    let nothing_matched = output.stdout.is_empty() && output.stderr.is_empty();
+    if !output.status.success() && !nothing_matched {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(PortsError::system_error(format!(
+            "lsof failed: {}",
+            stderr.trim()
+        )));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Parses lsof -F output format.
+///
+/// Format uses single-letter prefixes:
+/// - p: PID
+/// - c: Command name
+/// - t: File type (IPv4 or IPv6, precedes the name)
+/// - n: Name (includes address and port like *:3000 or 127.0.0.1:8080)
+///
+/// Names of connected sockets (`local->remote`) are skipped, so for UDP
+/// only bound, unconnected sockets are reported.
+fn parse_lsof_output(output: &str, protocol: Protocol) -> Result<Vec<PortEntry>> {
+    let mut entries = Vec::new();
+    let mut current_pid: Option<u32> = None;
+    let mut current_name: Option<String> = None;
+    let mut current_family = IpFamily::V4;
+
+    for line in output.lines() {
+        if line.is_empty() {
+            continue;
+        }
+
+        let prefix = line.chars().next().unwrap_or(' ');
+        let value = &line[1..];
+
+        match prefix {
+            'p' => {
+                current_pid = value.parse().ok();
+            }
+            'c' => {
+                current_name = Some(value.to_string());
+            }
+            't' => {
+                current_family = if value == "IPv6" {
+                    IpFamily::V6
+                } else {
+                    IpFamily::V4
+                };
+            }
+            'n' => {
+                if value.contains("->") {
+                    continue;
+                }
+
+                // Parse address and port from name like "*:3000" or "127.0.0.1:8080"
+                if let Some((address, port)) = parse_socket_name(value, current_family) {
+                    if let (Some(pid), Some(name)) = (current_pid, current_name.as_ref()) {
+                        let process = ProcessInfo::new(pid, name.clone());
+                        let mut entry = PortEntry::new(port, protocol, process);
+                        if let Some(address) = address {
+                            entry = entry.with_bind_address(address);
+                        }
+                        push_unique(&mut entries, entry);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Splits an lsof name field into bind address and port.
+///
+/// The wildcard `*` resolves to the unspecified address of `family`.
+/// The address is `None` if it cannot be parsed; the entry is still kept.
+///
+/// Examples:
+/// - "*:3000" -> (0.0.0.0 or ::, 3000)
+/// - "127.0.0.1:8080" -> (127.0.0.1, 8080)
+/// - "[::1]:9000" -> (::1, 9000)
+fn parse_socket_name(name: &str, family: IpFamily) -> Option<(Option<IpAddr>, u16)> {
+    // Find the last colon and parse the port after it
+    let (host, port) = name.rsplit_once(':')?;
+    Some((parse_host(host, family), port.parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn address(text: &str) -> Option<IpAddr> {
+        Some(text.parse().unwrap())
+    }
+
+    #[test]
+    fn test_parse_socket_name_wildcard() {
+        assert_eq!(
+            parse_socket_name("*:3000", IpFamily::V4),
+            Some((address("0.0.0.0"), 3000))
+        );
+        assert_eq!(
+            parse_socket_name("*:3000", IpFamily::V6),
+            Some((address("::"), 3000))
+        );
+    }
+
+    #[test]
+    fn test_parse_socket_name_ipv4() {
+        assert_eq!(
+            parse_socket_name("127.0.0.1:8080", IpFamily::V4),
+            Some((address("127.0.0.1"), 8080))
+        );
+    }
+
+    #[test]
+    fn test_parse_socket_name_ipv6() {
+        assert_eq!(
+            parse_socket_name("[::1]:9000", IpFamily::V6),
+            Some((address("::1"), 9000))
+        );
+        assert_eq!(
+            parse_socket_name("[fe80::1%lo0]:9000", IpFamily::V6),
+            Some((address("fe80::1"), 9000))
+        );
+    }
+
+    #[test]
+    fn test_parse_lsof_output() {
+        let output = "p1234\ncnode\ntIPv6\nn*:3000\np5678\ncpython\ntIPv4\nn127.0.0.1:8080\n";
+        let entries = parse_lsof_output(output, Protocol::Tcp).unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].port, 3000);
+        assert_eq!(entries[0].bind_address, address("::"));
+        assert_eq!(entries[0].family, Some(IpFamily::V6));
+        assert_eq!(entries[0].process.pid, 1234);
+        assert_eq!(entries[0].process.name, "node");
+        assert_eq!(entries[1].port, 8080);
+        assert_eq!(entries[1].bind_address, address("127.0.0.1"));
+        assert_eq!(entries[1].family, Some(IpFamily::V4));
+        assert_eq!(entries[1].process.pid, 5678);
+        assert_eq!(entries[1].process.name, "python");
+    }
+
+    #[test]
+    fn test_parse_lsof_output_deduplicates_same_socket() {
+        let output = "p1234\ncnode\ntIPv4\nn*:3000\ntIPv4\nn*:3000\ntIPv6\nn*:3000\n";
+        let entries = parse_lsof_output(output, Protocol::Tcp).unwrap();
+
+        // The two IPv4 descriptors collapse; the IPv6 listener is distinct
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].bind_address, address("0.0.0.0"));
+        assert_eq!(entries[1].bind_address, address("::"));
+    }
+
+    #[test]
+    fn test_parse_lsof_output_udp_skips_connected() {
+        let output = "p7498\ncpython3\nn127.0.0.1:5353\nn127.0.0.1:38237->127.0.0.1:5353\n";
+        let entries = parse_lsof_output(output, Protocol::Udp).unwrap();
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].port, 5353);
+        assert_eq!(entries[0].protocol, Protocol::Udp);
+    }
+}
@@ -0,0 +1,547 @@
+//! Netlink sock_diag port scanning backend.
+//!
+//! Queries the kernel's `NETLINK_SOCK_DIAG` interface (inet_diag) for
+//! listening TCP sockets and unconnected UDP sockets. Unlike the procfs backend, the state filter is
+//! applied inside the kernel, so only listening sockets are transferred
+//! even on hosts with tens of thousands of open connections. Socket owners
+//! are then resolved through `/proc/<pid>/fd`.
+
+use super::procfs::{map_socket_inodes_to_pids, read_process_name, PROC_ROOT};
+use super::{push_unique, ScannerBackend};
+use crate::error::{PortsError, Result};
+use crate::models::{ListenQueue, PortEntry, ProcessInfo, Protocol};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::path::Path;
+
+/// Size of `struct nlmsghdr`.
+const NLMSG_HEADER_LEN: usize = 16;
+/// Size of `struct inet_diag_req_v2`.
+const INET_DIAG_REQUEST_LEN: usize = 56;
+/// Size of `struct inet_diag_msg` (without trailing attributes).
+const INET_DIAG_MESSAGE_LEN: usize = 72;
+
+/// Netlink message type signalling an error (or an acknowledgement).
+const NLMSG_ERROR: u16 = 2;
+/// Netlink message type terminating a multipart dump.
+const NLMSG_DONE: u16 = 3;
+/// sock_diag request/response message type.
+const SOCK_DIAG_BY_FAMILY: u16 = 20;
+
+/// Request flag: this message is a request.
+const NLM_F_REQUEST: u16 = 0x001;
+/// Request flag: dump all matching entries (`NLM_F_ROOT | NLM_F_MATCH`).
+const NLM_F_DUMP: u16 = 0x300;
+
+/// Kernel TCP state number for LISTEN (`TCP_LISTEN` in `net/tcp_states.h`).
+const TCP_LISTEN: u32 = 10;
+/// Kernel state number of unconnected UDP sockets (`TCP_CLOSE`).
+const UDP_UNCONNECTED: u32 = 7;
+/// IP protocol numbers.
+const IPPROTO_TCP: u8 = 6;
+const IPPROTO_UDP: u8 = 17;
+/// Address family numbers as used by Linux.
+const AF_INET: u8 = 2;
+const AF_INET6: u8 = 10;
+
+/// Receive buffer size; the kernel sends dumps in chunks of about one page.
+const RECEIVE_BUFFER_LEN: usize = 32 * 1024;
+
+/// A listening socket reported by inet_diag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct DiagSocket {
+    /// The local port number
+    port: u16,
+    /// The local address the socket is bound to
+    address: IpAddr,
+    /// The socket protocol
+    protocol: Protocol,
+    /// The socket inode, used to find the owning process
+    inode: u64,
+    /// The user ID that created the socket
+    uid: u32,
+    /// Connections waiting to be accepted (TCP) or queued bytes (UDP)
+    pending: u32,
+    /// Maximum accept queue length (TCP) or queued send bytes (UDP)
+    backlog: u32,
+}
+
+/// Scanner backend that queries the kernel over netlink.
+pub struct NetlinkBackend;
+
+impl ScannerBackend for NetlinkBackend {
+    fn name(&self) -> &'static str {
+        "netlink"
+    }
+
+    fn is_available(&self) -> bool {
+        cfg!(target_os = "linux")
+    }
+
+    fn scan(&self) -> Result<Vec<PortEntry>> {
+        let sockets = query_listening_sockets()?;
+        let proc_root = Path::new(PROC_ROOT);
+        let owners = map_socket_inodes_to_pids(proc_root);
+
+        let mut entries = Vec::new();
+        for socket in &sockets {
+            let Some(&pid) = owners.get(&socket.inode) else {
+                continue;
+            };
+
+            let name = read_process_name(proc_root, pid).unwrap_or_else(|| pid.to_string());
+            let mut process = ProcessInfo::new(pid, name);
+            process.uid = Some(socket.uid);
+
+            let mut entry = PortEntry::new(socket.port, socket.protocol, process)
+                .with_bind_address(socket.address);
+            if socket.protocol == Protocol::Tcp {
+                entry.listen_queue = Some(ListenQueue {
+                    pending: socket.pending,
+                    backlog: socket.backlog,
+                });
+            }
+            push_unique(&mut entries, entry);
+        }
+
+        Ok(entries)
+    }
+}
+
+/// Queries listening TCP and unconnected UDP sockets for IPv4 and IPv6.
+#[cfg(target_os = "linux")]
+fn query_listening_sockets() -> Result<Vec<DiagSocket>> {
+    let socket = linux::NetlinkSocket::open()?;
+    let mut sockets = Vec::new();
+    let mut buffer = vec![0u8; RECEIVE_BUFFER_LEN];
+    let queries = [
+        (AF_INET, Protocol::Tcp),
+        (AF_INET6, Protocol::Tcp),
+        (AF_INET, Protocol::Udp),
+        (AF_INET6, Protocol::Udp),
+    ];
+
+    for (sequence, (family, protocol)) in queries.into_iter().enumerate() {
+        socket.send(&build_request(family, protocol, sequence as u32 + 1))?;
+
+        loop {
+            let received = socket.receive(&mut buffer)?;
+            match parse_response(&buffer[..received], protocol, &mut sockets) {
+                Ok(true) => break,
+                Ok(false) if received == 0 => break,
+                Ok(false) => continue,
+                // IPv6 dumps fail when the kernel has IPv6 disabled
+                Err(_) if family == AF_INET6 => break,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    Ok(sockets)
+}
+
+/// Netlink is Linux-only.
+#[cfg(not(target_os = "linux"))]
+fn query_listening_sockets() -> Result<Vec<DiagSocket>> {
+    Err(PortsError::system_error(
+        "The netlink backend is only available on Linux",
+    ))
+}
+
+/// Builds a `SOCK_DIAG_BY_FAMILY` dump request for listening sockets.
+fn build_request(family: u8, protocol: Protocol, sequence: u32) -> Vec<u8> {
+    let (ip_protocol, state) = match protocol {
+        Protocol::Tcp => (IPPROTO_TCP, TCP_LISTEN),
+        Protocol::Udp => (IPPROTO_UDP, UDP_UNCONNECTED),
+    };
+
+    let total_len = NLMSG_HEADER_LEN + INET_DIAG_REQUEST_LEN;
+    let mut request = Vec::with_capacity(total_len);
+
+    // struct nlmsghdr
+    request.extend((total_len as u32).to_ne_bytes());
+    request.extend(SOCK_DIAG_BY_FAMILY.to_ne_bytes());
+    request.extend((NLM_F_REQUEST | NLM_F_DUMP).to_ne_bytes());
+    request.extend(sequence.to_ne_bytes());
+    request.extend(0u32.to_ne_bytes()); // port ID, assigned by the kernel
+
+    // struct inet_diag_req_v2
+    request.push(family);
+    request.push(ip_protocol);
+    request.push(0); // idiag_ext: no extended attributes
+    request.push(0); // padding
+    request.extend((1u32 << state).to_ne_bytes());
+
+    // A zeroed inet_diag_sockid matches every socket
+    request.resize(total_len, 0);
+    request
+}
+
+/// Parses a buffer of netlink messages, appending any sockets found.
+///
+/// Returns `Ok(true)` once the end of the dump (`NLMSG_DONE`) is reached.
+fn parse_response(
+    buffer: &[u8],
+    protocol: Protocol,
+    sockets: &mut Vec<DiagSocket>,
+) -> Result<bool> {
+    let mut offset = 0;
+
+    while offset + NLMSG_HEADER_LEN <= buffer.len() {
+        let message_len = read_u32(buffer, offset) as usize;
+        let message_type = u16::from_ne_bytes([buffer[offset + 4], buffer[offset + 5]]);
+
+        if message_len < NLMSG_HEADER_LEN || offset + message_len > buffer.len() {
+            return Err(PortsError::system_error("Truncated netlink message"));
+        }
+
+        let payload = &buffer[offset + NLMSG_HEADER_LEN..offset + message_len];
+        match message_type {
+            NLMSG_DONE => return Ok(true),
+            NLMSG_ERROR => {
+                let errno = if payload.len() >= 4 {
+                    read_u32(payload, 0) as i32
+                } else {
+                    0
+                };
+                if errno != 0 {
+                    return Err(std::io::Error::from_raw_os_error(-errno).into());
+                }
+            }
+            SOCK_DIAG_BY_FAMILY => sockets.extend(parse_diag_message(payload, protocol)),
+            _ => {}
+        }
+
+        // Messages are aligned to 4 bytes
+        offset += (message_len + 3) & !3;
+    }
+
+    Ok(false)
+}
+
+/// Parses a `struct inet_diag_msg`.
+///
+/// Layout: family, state, timer, retrans (1 byte each), `inet_diag_sockid`
+/// (48 bytes, source port at offset 4 and the 16-byte source address at
+/// offset 8, both in network byte order), then expires, rqueue, wqueue,
+/// uid and inode (4 bytes each). For listening sockets rqueue is the
+/// current accept queue and wqueue the backlog.
+fn parse_diag_message(payload: &[u8], protocol: Protocol) -> Option<DiagSocket> {
+    if payload.len() < INET_DIAG_MESSAGE_LEN {
+        return None;
+    }
+
+    let address = match payload[0] {
+        AF_INET => IpAddr::V4(Ipv4Addr::new(
+            payload[8],
+            payload[9],
+            payload[10],
+            payload[11],
+        )),
+        AF_INET6 => {
+            let mut octets = [0u8; 16];
+            octets.copy_from_slice(&payload[8..24]);
+            IpAddr::V6(Ipv6Addr::from(octets))
+        }
+        _ => return None,
+    };
+
+    Some(DiagSocket {
+        port: u16::from_be_bytes([payload[4], payload[5]]),
+        address,
+        protocol,
+        pending: read_u32(payload, 56),
+        backlog: read_u32(payload, 60),
+        uid: read_u32(payload, 64),
+        inode: u64::from(read_u32(payload, 68)),
+    })
+}
+
+/// Reads a native-endian u32 at `offset`.
+fn read_u32(buffer: &[u8], offset: usize) -> u32 {
+    let mut bytes = [0u8; 4];
+    bytes.copy_from_slice(&buffer[offset..offset + 4]);
+    u32::from_ne_bytes(bytes)
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    //! Thin wrapper over the raw netlink socket syscalls.
+
+    use crate::error::{PortsError, Result};
+    use std::io;
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+
+    /// A `NETLINK_SOCK_DIAG` socket, closed on drop.
+    pub struct NetlinkSocket {
+        fd: OwnedFd,
+    }
+
+    impl NetlinkSocket {
+        /// Opens a new sock_diag netlink socket.
+        pub fn open() -> Result<Self> {
+            // SAFETY: socket() has no memory-safety preconditions.
+            let fd = unsafe {
+                libc::socket(
+                    libc::AF_NETLINK,
+                    libc::SOCK_DGRAM | libc::SOCK_CLOEXEC,
+                    libc::NETLINK_SOCK_DIAG,
+                )
+            };
+            if fd < 0 {
+                return Err(PortsError::system_error(format!(
+                    "Failed to open netlink socket: {}",
+                    io::Error::last_os_error()
+                )));
+            }
+
+            // SAFETY: fd is a freshly created, valid descriptor we own.
+            let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+            Ok(Self { fd })
+        }
+
+        /// Sends a request to the kernel.
+        pub fn send(&self, request: &[u8]) -> Result<()> {
+            // SAFETY: the pointer and length describe a valid, initialized slice.
+            let sent = unsafe {
+                libc::send(
+                    self.fd.as_raw_fd(),
+                    request.as_ptr().cast(),
+                    request.len(),
+                    0,
+                )
+            };
+            if sent < 0 {
+                return Err(io::Error::last_os_error().into());
+            }
+            Ok(())
+        }
+
+        /// Receives the next chunk of the response, returning its length.
+        pub fn receive(&self, buffer: &mut [u8]) -> Result<usize> {
+            loop {
+                // SAFETY: the pointer and length describe a valid, writable slice.
+                let received = unsafe {
+                    libc::recv(
+                        self.fd.as_raw_fd(),
+                        buffer.as_mut_ptr().cast(),
+                        buffer.len(),
+                        0,
+                    )
+                };
+                if received >= 0 {
+                    return Ok(received as usize);
+                }
+
+                let error = io::Error::last_os_error();
+                if error.kind() != io::ErrorKind::Interrupted {
+                    return Err(error.into());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a netlink message with the given type and payload.
+    fn netlink_message(message_type: u16, payload: &[u8]) -> Vec<u8> {
+        let len = NLMSG_HEADER_LEN + payload.len();
+        let mut message = Vec::new();
+        message.extend((len as u32).to_ne_bytes());
+        message.extend(message_type.to_ne_bytes());
+        message.extend(0u16.to_ne_bytes());
+        message.extend(1u32.to_ne_bytes());
+        message.extend(0u32.to_ne_bytes());
+        message.extend(payload);
+        message.resize((len + 3) & !3, 0);
+        message
+    }
+
+    /// Builds an `inet_diag_msg` payload for a listening socket.
+    fn diag_payload(port: u16, inode: u32, uid: u32, pending: u32, backlog: u32) -> Vec<u8> {
+        let mut payload = vec![0u8; INET_DIAG_MESSAGE_LEN];
+        payload[0] = AF_INET;
+        payload[1] = TCP_LISTEN as u8;
+        payload[4..6].copy_from_slice(&port.to_be_bytes());
+        payload[8..12].copy_from_slice(&[127, 0, 0, 1]);
+        payload[56..60].copy_from_slice(&pending.to_ne_bytes());
+        payload[60..64].copy_from_slice(&backlog.to_ne_bytes());
+        payload[64..68].copy_from_slice(&uid.to_ne_bytes());
+        payload[68..72].copy_from_slice(&inode.to_ne_bytes());
+        payload
+    }
+
+    #[test]
+    fn test_build_request() {
+        let request = build_request(AF_INET6, Protocol::Tcp, 7);
+
+        assert_eq!(request.len(), NLMSG_HEADER_LEN + INET_DIAG_REQUEST_LEN);
+        assert_eq!(read_u32(&request, 0) as usize, request.len());
+        assert_eq!(read_u32(&request, 8), 7);
+        assert_eq!(request[16], AF_INET6);
+        assert_eq!(request[17], IPPROTO_TCP);
+        assert_eq!(read_u32(&request, 20), 1 << TCP_LISTEN);
+    }
+
+    #[test]
+    fn test_build_request_udp() {
+        let request = build_request(AF_INET, Protocol::Udp, 3);
+
+        assert_eq!(request[16], AF_INET);
+        assert_eq!(request[17], IPPROTO_UDP);
+        assert_eq!(read_u32(&request, 20), 1 << UDP_UNCONNECTED);
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let mut buffer = netlink_message(
+            SOCK_DIAG_BY_FAMILY,
+            &diag_payload(3000, 20481, 1000, 2, 511),
+        );
+        buffer.extend(netlink_message(
+            SOCK_DIAG_BY_FAMILY,
+            &diag_payload(5432, 30001, 70, 0, 128),
+        ));
+
+        let mut sockets = Vec::new();
+        let done = parse_response(&buffer, Protocol::Tcp, &mut sockets).unwrap();
+
+        assert!(!done);
+        assert_eq!(
+            sockets,
+            vec![
+                DiagSocket {
+                    port: 3000,
+                    address: IpAddr::V4(Ipv4Addr::LOCALHOST),
+                    protocol: Protocol::Tcp,
+                    inode: 20481,
+                    uid: 1000,
+                    pending: 2,
+                    backlog: 511
+                },
+                DiagSocket {
+                    port: 5432,
+                    address: IpAddr::V4(Ipv4Addr::LOCALHOST),
+                    protocol: Protocol::Tcp,
+                    inode: 30001,
+                    uid: 70,
+                    pending: 0,
+                    backlog: 128
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_diag_message_ipv6() {
+        let mut payload = diag_payload(8080, 7, 0, 0, 10);
+        payload[0] = AF_INET6;
+        payload[8..24].copy_from_slice(&Ipv6Addr::LOCALHOST.octets());
+
+        let socket = parse_diag_message(&payload, Protocol::Tcp).unwrap();
+        assert_eq!(socket.address, IpAddr::V6(Ipv6Addr::LOCALHOST));
+    }
+
+    #[test]
+    fn test_parse_response_done() {
+        let mut buffer = netlink_message(SOCK_DIAG_BY_FAMILY, &diag_payload(8080, 1, 0, 0, 10));
+        buffer.extend(netlink_message(NLMSG_DONE, &0u32.to_ne_bytes()));
+
+        let mut sockets = Vec::new();
+        assert!(parse_response(&buffer, Protocol::Tcp, &mut sockets).unwrap());
+        assert_eq!(sockets.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_response_error() {
+        let buffer = netlink_message(NLMSG_ERROR, &(-13i32).to_ne_bytes());
+
+        let mut sockets = Vec::new();
+        assert!(parse_response(&buffer, Protocol::Tcp, &mut sockets).is_err());
+    }
+
+    #[test]
+    fn test_parse_response_truncated() {
+        let mut buffer = netlink_message(SOCK_DIAG_BY_FAMILY, &diag_payload(8080, 1, 0, 0, 10));
+        buffer.truncate(40);
+
+        let mut sockets = Vec::new();
+        assert!(parse_response(&buffer, Protocol::Tcp, &mut sockets).is_err());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_scan_finds_own_listener() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let entries = NetlinkBackend.scan().unwrap();
+        let entry = entries
+            .iter()
+            .find(|e| e.port == port)
+            .expect("listener not found");
+
+        assert_eq!(entry.process.pid, std::process::id());
+        assert_eq!(entry.address_display(), "127.0.0.1");
+        assert!(entry.listen_queue.is_some());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_scan_finds_own_udp_socket() {
+        let socket = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let port = socket.local_addr().unwrap().port();
+
+        let entries = NetlinkBackend.scan().unwrap();
+        let entry = entries
+            .iter()
+            .find(|e| e.port == port && e.protocol == Protocol::Udp)
+            .expect("UDP socket not found");
+
+        assert_eq!(entry.process.pid, std::process::id());
+        assert!(entry.listen_queue.is_none());
+    }
+
+    /// Compares backends on a socket-heavy workload.
+    ///
+    /// Run with `cargo test --release -- --ignored --nocapture bench_`.
+    #[cfg(target_os = "linux")]
+    #[test]
+    #[ignore = "benchmark"]
+    fn bench_socket_heavy_workload() {
+        use super::super::{LsofBackend, ProcfsBackend};
+        use std::net::{TcpListener, TcpStream};
+        use std::time::Instant;
+
+        const LISTENER_COUNT: usize = 2_000;
+        const CONNECTION_COUNT: usize = 4_000;
+
+        let listeners: Vec<TcpListener> = (0..LISTENER_COUNT)
+            .map(|_| TcpListener::bind("127.0.0.1:0").unwrap())
+            .collect();
+        let target = listeners[0].local_addr().unwrap();
+        let mut connections = Vec::with_capacity(CONNECTION_COUNT * 2);
+        for _ in 0..CONNECTION_COUNT {
+            connections.push(TcpStream::connect(target).unwrap());
+            connections.push(listeners[0].accept().unwrap().0);
+        }
+
+        let backends: [&dyn ScannerBackend; 3] = [&NetlinkBackend, &ProcfsBackend, &LsofBackend];
+        for backend in backends {
+            if !backend.is_available() {
+                println!("{:>8}: unavailable", backend.name());
+                continue;
+            }
+            let started = Instant::now();
+            let entries = backend.scan().unwrap();
+            println!(
+                "{:>8}: {} listeners in {:?}",
+                backend.name(),
+                entries.len(),
+                started.elapsed()
+            );
+        }
+    }
+}
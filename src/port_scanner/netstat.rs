@@ -0,0 +1,341 @@
+//! netstat port scanning backend.
+//!
+//! Uses `netstat -tulnp` on Linux (net-tools) and `netstat -anv -p tcp|udp`
+//! on macOS. The two variants print different layouts, so each has its
+//! own parser.
+
+use super::{command_exists, parse_host, push_unique, run_command, ScannerBackend};
+use crate::error::Result;
+use crate::models::{IpFamily, PortEntry, ProcessInfo, Protocol};
+use std::collections::HashMap;
+
+/// TCP state column value for listening sockets.
+const LISTEN_STATE: &str = "LISTEN";
+
+/// Scanner backend based on `netstat`.
+pub struct NetstatBackend;
+
+impl ScannerBackend for NetstatBackend {
+    fn name(&self) -> &'static str {
+        "netstat"
+    }
+
+    fn is_available(&self) -> bool {
+        command_exists("netstat")
+    }
+
+    fn scan(&self) -> Result<Vec<PortEntry>> {
+        if cfg!(target_os = "linux") {
+            // -t, -u: TCP and UDP
+            // -l: Only listening (TCP) and unconnected (UDP) sockets
+            // -n: Don't resolve names
+            // -p: Show PID/Program name
+            let stdout = run_command("netstat", &["-tulnp"])?;
+            Ok(parse_netstat_linux_output(&stdout))
+        } else {
+            // -a: Include listening sockets
+            // -n: Don't resolve names
+            // -v: Verbose (adds the pid column)
+            let mut entries = Vec::new();
+            for protocol in ["tcp", "udp"] {
+                let stdout = run_command("netstat", &["-anv", "-p", protocol])?;
+                entries.extend(parse_netstat_bsd_output(&stdout));
+            }
+            fill_missing_process_names(&mut entries);
+            Ok(entries)
+        }
+    }
+}
+
+/// Returns the protocol for a netstat `Proto` column value (`tcp6`, `udp4`...).
+fn parse_protocol(proto: &str) -> Option<Protocol> {
+    if proto.starts_with("tcp") {
+        Some(Protocol::Tcp)
+    } else if proto.starts_with("udp") {
+        Some(Protocol::Udp)
+    } else {
+        None
+    }
+}
+
+/// Returns the family of a wildcard (`*`) local address for a `Proto`
+/// column value.
+///
+/// `tcp4`/`udp4` and plain `tcp`/`udp` sockets are IPv4; `tcp6`, `udp6` and
+/// macOS dual-stack `tcp46`/`udp46` sockets are IPv6.
+fn wildcard_family(proto: &str) -> IpFamily {
+    if proto.contains('6') {
+        IpFamily::V6
+    } else {
+        IpFamily::V4
+    }
+}
+
+/// Parses Linux `netstat -tulnp` output.
+///
+/// Data lines look like:
+/// `tcp6  0  0 :::3000  :::*  LISTEN  5678/node`
+/// `udp   0  0 127.0.0.1:5353  0.0.0.0:*  7446/python3`
+///
+/// UDP lines have no state column. The program column is `-` when the
+/// owner is not visible to the current user; such sockets are skipped.
+fn parse_netstat_linux_output(output: &str) -> Vec<PortEntry> {
+    let mut entries = Vec::new();
+
+    for line in output.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 6 {
+            continue;
+        }
+
+        let (protocol, program_column) = match parse_protocol(fields[0]) {
+            Some(Protocol::Tcp) if fields[5] == LISTEN_STATE => (Protocol::Tcp, 6),
+            // A state on a UDP line means the socket is connected
+            Some(Protocol::Udp) if fields[5] == "-" || fields[5].contains('/') => {
+                (Protocol::Udp, 5)
+            }
+            _ => continue,
+        };
+        if fields.len() <= program_column {
+            continue;
+        }
+
+        let Some((host, port)) = fields[3].rsplit_once(':') else {
+            continue;
+        };
+        let Ok(port) = port.parse() else {
+            continue;
+        };
+        let address = parse_host(host, wildcard_family(fields[0]));
+
+        // Program names may contain spaces, so rejoin the remaining fields
+        let program = fields[program_column..].join(" ");
+        let Some((pid, name)) = program.split_once('/') else {
+            continue;
+        };
+        let Ok(pid) = pid.parse() else {
+            continue;
+        };
+
+        let process = ProcessInfo::new(pid, name);
+        let mut entry = PortEntry::new(port, protocol, process);
+        if let Some(address) = address {
+            entry = entry.with_bind_address(address);
+        }
+        push_unique(&mut entries, entry);
+    }
+
+    entries
+}
+
+/// Parses macOS `netstat -anv -p tcp` or `netstat -anv -p udp` output.
+///
+/// The position of the pid column differs between macOS releases, so it is
+/// located from the header. Older releases print a bare PID; newer ones
+/// print `process:pid`. When only the PID is known the process name is left
+/// empty for [`fill_missing_process_names`] to resolve.
+///
+/// UDP lines leave the state column empty, shifting later columns left by
+/// one; only unconnected UDP sockets (foreign address `*.*`) are kept.
+fn parse_netstat_bsd_output(output: &str) -> Vec<PortEntry> {
+    let mut entries = Vec::new();
+    let mut pid_column = None;
+
+    for line in output.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+
+        if fields.first() == Some(&"Proto") {
+            pid_column = find_bsd_pid_column(&fields);
+            continue;
+        }
+
+        let Some(tcp_pid_column) = pid_column else {
+            continue;
+        };
+        if fields.len() < 6 {
+            continue;
+        }
+
+        let (protocol, pid_column) = match parse_protocol(fields[0]) {
+            Some(Protocol::Tcp) if fields[5] == LISTEN_STATE => (Protocol::Tcp, tcp_pid_column),
+            Some(Protocol::Udp) if fields[4] == "*.*" => (Protocol::Udp, tcp_pid_column - 1),
+            _ => continue,
+        };
+        if fields.len() <= pid_column {
+            continue;
+        }
+
+        // Local address is "127.0.0.1.5432", "*.3000" or "::1.8080"
+        let Some((host, port)) = fields[3].rsplit_once('.') else {
+            continue;
+        };
+        let Ok(port) = port.parse() else {
+            continue;
+        };
+        let address = parse_host(host, wildcard_family(fields[0]));
+
+        let (name, pid) = match fields[pid_column].rsplit_once(':') {
+            Some((name, pid)) => (name, pid),
+            None => ("", fields[pid_column]),
+        };
+        let Ok(pid) = pid.parse() else {
+            continue;
+        };
+
+        let process = ProcessInfo::new(pid, name);
+        let mut entry = PortEntry::new(port, protocol, process);
+        if let Some(address) = address {
+            entry = entry.with_bind_address(address);
+        }
+        push_unique(&mut entries, entry);
+    }
+
+    entries
+}
+
+/// Finds the index of the pid column in a data line from the header fields.
+///
+/// The header spells "Local Address" and "Foreign Address" as two words
+/// each, while data lines have a single field for each address.
+fn find_bsd_pid_column(header: &[&str]) -> Option<usize> {
+    let header_index = header
+        .iter()
+        .position(|field| *field == "pid" || *field == "process:pid")?;
+    // At least one column must remain for UDP lines, which lack a state
+    header_index.checked_sub(2).filter(|column| *column > 0)
+}
+
+/// Resolves empty process names with a single `ps` call.
+fn fill_missing_process_names(entries: &mut [PortEntry]) {
+    let pids: Vec<String> = entries
+        .iter()
+        .filter(|e| e.process.name.is_empty())
+        .map(|e| e.process.pid.to_string())
+        .collect();
+    if pids.is_empty() {
+        return;
+    }
+
+    let Ok(stdout) = run_command("ps", &["-o", "pid=,comm=", "-p", &pids.join(",")]) else {
+        return;
+    };
+    let names = parse_ps_names(&stdout);
+
+    for entry in entries.iter_mut().filter(|e| e.process.name.is_empty()) {
+        if let Some(name) = names.get(&entry.process.pid) {
+            entry.process.name = name.clone();
+        }
+    }
+}
+
+/// Parses `ps -o pid=,comm=` output into a PID to executable name map.
+fn parse_ps_names(output: &str) -> HashMap<u32, String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (pid, comm) = line.trim().split_once(char::is_whitespace)?;
+            let name = comm.trim().rsplit('/').next()?;
+            Some((pid.parse().ok()?, name.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NETSTAT_LINUX_OUTPUT: &str = "\
+Active Internet connections (only servers)
+Proto Recv-Q Send-Q Local Address           Foreign Address         State       PID/Program name
+tcp        0      0 127.0.0.1:5432          0.0.0.0:*               LISTEN      1234/postgres
+tcp        0      0 0.0.0.0:22              0.0.0.0:*               LISTEN      -
+tcp6       0      0 :::3000                 :::*                    LISTEN      5678/node
+tcp6       0      0 :::5432                 :::*                    LISTEN      1234/postgres
+udp        0      0 127.0.0.1:5353          0.0.0.0:*                           7446/python3
+udp6       0      0 :::8125                 :::*                                -
+udp        0      0 10.0.0.2:41000          10.0.0.1:53             ESTABLISHED 7446/python3
+";
+
+    const NETSTAT_BSD_OUTPUT: &str = "\
+Active Internet connections (including servers)
+Proto Recv-Q Send-Q  Local Address          Foreign Address        (state)     rhiwat shiwat    pid   epid  state    options
+tcp4       0      0  127.0.0.1.5432         *.*                    LISTEN      131072 131072    364      0 0x0080 0x00000006
+tcp46      0      0  *.3000                 *.*                    LISTEN      131072 131072  12345      0 0x0080 0x00000006
+tcp4       0      0  192.168.1.5.52144      17.57.146.20.5223      ESTABLISHED 131072 132432    411      0 0x0102 0x00000008
+udp4       0      0  *.5353                 *.*                                786896   9216    291      0 0x0100 0x00000000
+udp4       0      0  192.168.1.5.61234      1.1.1.1.53                         786896   9216    411      0 0x0100 0x00000000
+";
+
+    const NETSTAT_BSD_PROCESS_OUTPUT: &str = "\
+Active Internet connections (including servers)
+Proto Recv-Q Send-Q  Local Address          Foreign Address        (state)      rxbytes      txbytes  rhiwat  shiwat    process:pid     state      options           gencnt    flags   flags1 usscnt rtncnt fltrs
+tcp6       0      0  ::1.8080               *.*                    LISTEN             0            0  131072  131072         java:4321   00000 00000100 00000000001c7e0c 00000000 00000800      1      0 000001
+tcp4       0      0  *.5000                 *.*                    LISTEN             0            0  131072  131072  ControlCe:611   00000 00000100 00000000000012c8 00000000 00000800      1      0 000001
+";
+
+    fn summarize(entries: &[PortEntry]) -> Vec<(u16, Protocol, String, u32, &str)> {
+        entries
+            .iter()
+            .map(|e| {
+                let address = e.address_display();
+                (
+                    e.port,
+                    e.protocol,
+                    address,
+                    e.process.pid,
+                    e.process.name.as_str(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_netstat_linux_output() {
+        let entries = parse_netstat_linux_output(NETSTAT_LINUX_OUTPUT);
+
+        assert_eq!(
+            summarize(&entries),
+            vec![
+                (5432, Protocol::Tcp, "127.0.0.1".into(), 1234, "postgres"),
+                (3000, Protocol::Tcp, "::".into(), 5678, "node"),
+                (5432, Protocol::Tcp, "::".into(), 1234, "postgres"),
+                (5353, Protocol::Udp, "127.0.0.1".into(), 7446, "python3"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_netstat_bsd_output() {
+        let entries = parse_netstat_bsd_output(NETSTAT_BSD_OUTPUT);
+
+        assert_eq!(
+            summarize(&entries),
+            vec![
+                (5432, Protocol::Tcp, "127.0.0.1".into(), 364, ""),
+                (3000, Protocol::Tcp, "::".into(), 12345, ""),
+                (5353, Protocol::Udp, "0.0.0.0".into(), 291, ""),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_netstat_bsd_output_with_process_names() {
+        let entries = parse_netstat_bsd_output(NETSTAT_BSD_PROCESS_OUTPUT);
+
+        assert_eq!(
+            summarize(&entries),
+            vec![
+                (8080, Protocol::Tcp, "::1".into(), 4321, "java"),
+                (5000, Protocol::Tcp, "0.0.0.0".into(), 611, "ControlCe"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_ps_names() {
+        let names = parse_ps_names("  364 /usr/local/bin/postgres\n12345 node\n");
+
+        assert_eq!(names.get(&364).map(String::as_str), Some("postgres"));
+        assert_eq!(names.get(&12345).map(String::as_str), Some("node"));
+    }
+}
@@ -0,0 +1,408 @@
+//! Linux procfs port scanning backend.
+//!
+//! Reads listening TCP sockets and bound UDP sockets directly from
+//! `/proc/net/{tcp,tcp6,udp,udp6}` and maps socket inodes back to their
+//! owning processes via `/proc/<pid>/fd`, so no external binary such as
+//! `lsof` is required.
+
+use super::{push_unique, ScannerBackend};
+use crate::error::{PortsError, Result};
+use crate::models::{PortEntry, ProcessInfo, Protocol};
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::net::IpAddr;
+use std::path::Path;
+
+/// Default mount point of the proc filesystem.
+pub(super) const PROC_ROOT: &str = "/proc";
+
+/// Socket tables read from the proc filesystem, relative to its root.
+const SOCKET_TABLES: [(&str, Protocol); 4] = [
+    ("net/tcp", Protocol::Tcp),
+    ("net/tcp6", Protocol::Tcp),
+    ("net/udp", Protocol::Udp),
+    ("net/udp6", Protocol::Udp),
+];
+
+/// Kernel state code for a TCP socket in the LISTEN state.
+const TCP_LISTEN_STATE: &str = "0A";
+
+/// Kernel state code for an unconnected UDP socket (`TCP_CLOSE`).
+const UDP_UNCONNECTED_STATE: &str = "07";
+
+/// A listening socket parsed from a `/proc/net/{tcp,udp}*` table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ProcSocket {
+    /// The local port number
+    port: u16,
+    /// The local (bind) address
+    address: IpAddr,
+    /// The socket protocol
+    protocol: Protocol,
+    /// The socket inode, used to find the owning process
+    inode: u64,
+}
+
+/// Scanner backend that reads the Linux proc filesystem directly.
+pub struct ProcfsBackend;
+
+impl ScannerBackend for ProcfsBackend {
+    fn name(&self) -> &'static str {
+        "procfs"
+    }
+
+    fn is_available(&self) -> bool {
+        Path::new(PROC_ROOT).join(SOCKET_TABLES[0].0).exists()
+    }
+
+    fn scan(&self) -> Result<Vec<PortEntry>> {
+        scan_proc_root(Path::new(PROC_ROOT))
+    }
+}
+
+/// Scans a proc filesystem mounted at `proc_root`.
+///
+/// Sockets whose owner cannot be determined (typically processes of other
+/// users when running unprivileged) are skipped, matching what `lsof` reports.
+fn scan_proc_root(proc_root: &Path) -> Result<Vec<PortEntry>> {
+    let mut sockets = Vec::new();
+    for (table, protocol) in SOCKET_TABLES {
+        match fs::read_to_string(proc_root.join(table)) {
+            Ok(content) => sockets.extend(parse_proc_net(&content, protocol)),
+            // tcp6/udp6 are absent when IPv6 is disabled in the kernel
+            Err(e) if e.kind() == ErrorKind::NotFound => continue,
+            Err(e) if e.kind() == ErrorKind::PermissionDenied => {
+                return Err(PortsError::permission_denied(format!(
+                    "Cannot read {}: {}",
+                    proc_root.join(table).display(),
+                    e
+                )));
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    let owners = map_socket_inodes_to_pids(proc_root);
+    let mut entries: Vec<PortEntry> = Vec::new();
+
+    for socket in &sockets {
+        let Some(&pid) = owners.get(&socket.inode) else {
+            continue;
+        };
+
+        let name = read_process_name(proc_root, pid).unwrap_or_else(|| pid.to_string());
+        let process = ProcessInfo::new(pid, name);
+        let entry =
+            PortEntry::new(socket.port, socket.protocol, process).with_bind_address(socket.address);
+        push_unique(&mut entries, entry);
+    }
+
+    Ok(entries)
+}
+
+/// Parses the contents of a `/proc/net/{tcp,udp}{,6}` table.
+///
+/// Each data line has the form:
+/// `sl local_address rem_address st tx_queue:rx_queue tr:tm->when retrnsmt uid timeout inode ...`
+/// where `local_address` is `<hex ip>:<hex port>`. Only TCP sockets in the
+/// LISTEN state and unconnected UDP sockets are returned.
+fn parse_proc_net(content: &str, protocol: Protocol) -> Vec<ProcSocket> {
+    content
+        .lines()
+        .skip(1) // header line
+        .filter_map(|line| parse_proc_net_line(line, protocol))
+        .collect()
+}
+
+/// Parses a single data line of a `/proc/net/{tcp,udp}*` table.
+fn parse_proc_net_line(line: &str, protocol: Protocol) -> Option<ProcSocket> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    if fields.len() < 10 {
+        return None;
+    }
+
+    let local_address = fields[1];
+    let state = fields[3];
+    let inode = fields[9];
+
+    let listening_state = match protocol {
+        Protocol::Tcp => TCP_LISTEN_STATE,
+        Protocol::Udp => UDP_UNCONNECTED_STATE,
+    };
+    if state != listening_state {
+        return None;
+    }
+
+    let (address_hex, port_hex) = local_address.rsplit_once(':')?;
+    let address = parse_hex_address(address_hex)?;
+    let port = u16::from_str_radix(port_hex, 16).ok()?;
+    let inode = inode.parse().ok()?;
+
+    Some(ProcSocket {
+        port,
+        address,
+        protocol,
+        inode,
+    })
+}
+
+/// Decodes a hex-encoded address from a `/proc/net` table.
+///
+/// The kernel prints the address as 32-bit words in host byte order
+/// (one word for IPv4, four for IPv6), so each word is converted back to
+/// its in-memory bytes, which are in network order.
+fn parse_hex_address(hex: &str) -> Option<IpAddr> {
+    if !hex.len().is_multiple_of(8) {
+        return None;
+    }
+
+    let words = hex
+        .as_bytes()
+        .chunks(8)
+        .map(|chunk| {
+            let word = std::str::from_utf8(chunk).ok()?;
+            u32::from_str_radix(word, 16).ok()
+        })
+        .collect::<Option<Vec<u32>>>()?;
+    let bytes: Vec<u8> = words.iter().flat_map(|word| word.to_ne_bytes()).collect();
+
+    match bytes.len() {
+        4 => Some(IpAddr::from(<[u8; 4]>::try_from(bytes).ok()?)),
+        16 => Some(IpAddr::from(<[u8; 16]>::try_from(bytes).ok()?)),
+        _ => None,
+    }
+}
+
+/// Builds a map from socket inode to owning PID by reading `/proc/<pid>/fd`.
+///
+/// Processes whose file descriptors cannot be read (permission denied or
+/// exited during the scan) are silently skipped.
+pub(super) fn map_socket_inodes_to_pids(proc_root: &Path) -> HashMap<u64, u32> {
+    let mut owners = HashMap::new();
+
+    let Ok(proc_entries) = fs::read_dir(proc_root) else {
+        return owners;
+    };
+
+    for proc_entry in proc_entries.flatten() {
+        let Some(pid) = proc_entry
+            .file_name()
+            .to_str()
+            .and_then(|s| s.parse::<u32>().ok())
+        else {
+            continue;
+        };
+
+        let Ok(fds) = fs::read_dir(proc_entry.path().join("fd")) else {
+            continue;
+        };
+
+        for fd in fds.flatten() {
+            let Ok(target) = fs::read_link(fd.path()) else {
+                continue;
+            };
+            if let Some(inode) = parse_socket_link(&target.to_string_lossy()) {
+                owners.entry(inode).or_insert(pid);
+            }
+        }
+    }
+
+    owners
+}
+
+/// Extracts the inode from a file descriptor link target like `socket:[12345]`.
+fn parse_socket_link(target: &str) -> Option<u64> {
+    target
+        .strip_prefix("socket:[")?
+        .strip_suffix(']')?
+        .parse()
+        .ok()
+}
+
+/// Reads the executable name of a process from `/proc/<pid>/comm`.
+pub(super) fn read_process_name(proc_root: &Path, pid: u32) -> Option<String> {
+    let comm = fs::read_to_string(proc_root.join(pid.to_string()).join("comm")).ok()?;
+    let name = comm.trim_end_matches('\n');
+    if name.is_empty() {
+        None
+    } else {
+        Some(name.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::symlink;
+
+    const PROC_NET_TCP: &str = "\
+  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 0100007F:BC8F 00000000:0000 0A 00000000:00000000 00:00000000 00000000 65534        0 946 1 00000000e6d4f238 100 0 0 10 0
+   1: 00000000:0BB8 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 20481 1 0000000018ae7a5e 100 0 0 10 0
+   2: 0100007F:BC8F 0100007F:A062 01 00000000:00000000 00:00000000 00000000 65534        0 3216 2 000000008cf18eff 20 4 0 18 -1
+";
+
+    const PROC_NET_TCP6: &str = "\
+  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000000000000000000000000000:0BB8 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 20482 1 0000000000000000 100 0 0 10 0
+   1: 00000000000000000000000001000000:1F90 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 30001 1 0000000000000000 100 0 0 10 0
+";
+
+    const PROC_NET_UDP: &str = "\
+   sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode ref pointer drops
+ 2184: 0100007F:D3ED 0100007F:14E9 01 00000000:00000000 00:00000000 00000000     0        0 42197 2 00000000c1f2f848 0
+ 2436: 0100007F:14E9 00000000:0000 07 00000000:00000000 00:00000000 00000000     0        0 42194 2 00000000808cb09e 0
+";
+
+    fn write_process(proc_root: &Path, pid: u32, name: &str, inodes: &[u64]) {
+        let pid_dir = proc_root.join(pid.to_string());
+        fs::create_dir_all(pid_dir.join("fd")).unwrap();
+        fs::write(pid_dir.join("comm"), format!("{}\n", name)).unwrap();
+        symlink("/dev/null", pid_dir.join("fd").join("0")).unwrap();
+        for (index, inode) in inodes.iter().enumerate() {
+            let link = pid_dir.join("fd").join((index + 3).to_string());
+            symlink(format!("socket:[{}]", inode), link).unwrap();
+        }
+    }
+
+    fn proc_socket(address: &str, port: u16, protocol: Protocol, inode: u64) -> ProcSocket {
+        ProcSocket {
+            port,
+            address: address.parse().unwrap(),
+            protocol,
+            inode,
+        }
+    }
+
+    // The fixtures were captured on a little-endian host
+    #[cfg(target_endian = "little")]
+    #[test]
+    fn test_parse_hex_address() {
+        assert_eq!(parse_hex_address("0100007F"), "127.0.0.1".parse().ok());
+        assert_eq!(parse_hex_address("00000000"), "0.0.0.0".parse().ok());
+        assert_eq!(
+            parse_hex_address("00000000000000000000000001000000"),
+            "::1".parse().ok()
+        );
+        assert_eq!(
+            parse_hex_address("0000000000000000FFFF00000100007F"),
+            "::ffff:127.0.0.1".parse().ok()
+        );
+        assert_eq!(parse_hex_address("0100"), None);
+        assert_eq!(parse_hex_address("ZZZZZZZZ"), None);
+    }
+
+    #[cfg(target_endian = "little")]
+    #[test]
+    fn test_parse_proc_net_tcp_listen_only() {
+        let sockets = parse_proc_net(PROC_NET_TCP, Protocol::Tcp);
+
+        assert_eq!(
+            sockets,
+            vec![
+                proc_socket("127.0.0.1", 48271, Protocol::Tcp, 946),
+                proc_socket("0.0.0.0", 3000, Protocol::Tcp, 20481),
+            ]
+        );
+    }
+
+    #[cfg(target_endian = "little")]
+    #[test]
+    fn test_parse_proc_net_tcp6() {
+        let sockets = parse_proc_net(PROC_NET_TCP6, Protocol::Tcp);
+
+        assert_eq!(
+            sockets,
+            vec![
+                proc_socket("::", 3000, Protocol::Tcp, 20482),
+                proc_socket("::1", 8080, Protocol::Tcp, 30001),
+            ]
+        );
+    }
+
+    #[cfg(target_endian = "little")]
+    #[test]
+    fn test_parse_proc_net_udp_unconnected_only() {
+        let sockets = parse_proc_net(PROC_NET_UDP, Protocol::Udp);
+
+        assert_eq!(
+            sockets,
+            vec![proc_socket("127.0.0.1", 5353, Protocol::Udp, 42194)]
+        );
+    }
+
+    #[test]
+    fn test_parse_proc_net_ignores_malformed_lines() {
+        let content = "header\n   0: garbage\n   1: 00000000:ZZZZ 00000000:0000 0A 0 0 0 0 0 1 1\n";
+        assert!(parse_proc_net(content, Protocol::Tcp).is_empty());
+    }
+
+    #[test]
+    fn test_parse_socket_link() {
+        assert_eq!(parse_socket_link("socket:[12345]"), Some(12345));
+        assert_eq!(parse_socket_link("pipe:[6474]"), None);
+        assert_eq!(parse_socket_link("/dev/null"), None);
+    }
+
+    #[cfg(target_endian = "little")]
+    #[test]
+    fn test_scan_proc_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let proc_root = dir.path();
+        fs::create_dir_all(proc_root.join("net")).unwrap();
+        fs::write(proc_root.join("net/tcp"), PROC_NET_TCP).unwrap();
+        fs::write(proc_root.join("net/tcp6"), PROC_NET_TCP6).unwrap();
+        fs::write(proc_root.join("net/udp"), PROC_NET_UDP).unwrap();
+        write_process(proc_root, 1234, "node", &[20481, 20482]);
+        write_process(proc_root, 5678, "python3", &[30001, 42194, 42197]);
+        fs::create_dir_all(proc_root.join("self")).unwrap();
+
+        let mut entries = scan_proc_root(proc_root).unwrap();
+        entries.sort_by_key(|e| (e.port, e.family));
+
+        // Inode 946 has no visible owner and is skipped; the IPv4 and IPv6
+        // listeners on 3000 are reported separately.
+        let summary: Vec<(u16, Protocol, String, u32, &str)> = entries
+            .iter()
+            .map(|e| {
+                let address = e.address_display();
+                (
+                    e.port,
+                    e.protocol,
+                    address,
+                    e.process.pid,
+                    e.process.name.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (3000, Protocol::Tcp, "0.0.0.0".to_string(), 1234, "node"),
+                (3000, Protocol::Tcp, "::".to_string(), 1234, "node"),
+                (
+                    5353,
+                    Protocol::Udp,
+                    "127.0.0.1".to_string(),
+                    5678,
+                    "python3"
+                ),
+                (8080, Protocol::Tcp, "::1".to_string(), 5678, "python3"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scan_proc_root_without_tcp6() {
+        let dir = tempfile::tempdir().unwrap();
+        let proc_root = dir.path();
+        fs::create_dir_all(proc_root.join("net")).unwrap();
+        fs::write(proc_root.join("net/tcp"), PROC_NET_TCP).unwrap();
+        write_process(proc_root, 1234, "node", &[20481]);
+
+        let entries = scan_proc_root(proc_root).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].port, 3000);
+    }
+}
@@ -0,0 +1,169 @@
+//! ss port scanning backend.
+//!
+//! Uses `ss -ltunpH` from iproute2, which is present on most Linux
+//! distributions where `lsof` and `netstat` are not installed by default.
+
+use super::{command_exists, parse_host, push_unique, run_command, ScannerBackend};
+use crate::error::Result;
+use crate::models::{IpFamily, PortEntry, ProcessInfo, Protocol};
+
+/// Scanner backend based on `ss -ltunpH`.
+pub struct SsBackend;
+
+impl ScannerBackend for SsBackend {
+    fn name(&self) -> &'static str {
+        "ss"
+    }
+
+    fn is_available(&self) -> bool {
+        command_exists("ss")
+    }
+
+    fn scan(&self) -> Result<Vec<PortEntry>> {
+        // -l: Only listening (TCP) and unconnected (UDP) sockets
+        // -t, -u: TCP and UDP
+        // -n: Don't resolve names
+        // -p: Show owning processes
+        // -H: Suppress the header line
+        let stdout = run_command("ss", &["-ltunpH"])?;
+        Ok(parse_ss_output(&stdout))
+    }
+}
+
+/// Parses `ss -ltunpH` output.
+///
+/// Each line has the form:
+/// `tcp LISTEN 0 511 0.0.0.0:3000 0.0.0.0:* users:(("node",pid=1234,fd=20))`
+///
+/// A socket shared by several processes (e.g. pre-forked workers) lists
+/// every owner and produces one entry per PID. Sockets without a visible
+/// owner are skipped.
+fn parse_ss_output(output: &str) -> Vec<PortEntry> {
+    let mut entries = Vec::new();
+
+    for line in output.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 7 {
+            continue;
+        }
+
+        let protocol = match fields[0] {
+            "tcp" => Protocol::Tcp,
+            "udp" => Protocol::Udp,
+            _ => continue,
+        };
+
+        // Local address is "127.0.0.1:3000", "[::]:3000" or "*:3000"; ss
+        // prints "*" for dual-stack IPv6 sockets
+        let Some((host, port)) = fields[4].rsplit_once(':') else {
+            continue;
+        };
+        let Ok(port) = port.parse() else {
+            continue;
+        };
+        let address = parse_host(host, IpFamily::V6);
+
+        let process_field = fields[6..].join(" ");
+        for (pid, name) in parse_ss_users(&process_field) {
+            let process = ProcessInfo::new(pid, name);
+            let mut entry = PortEntry::new(port, protocol, process);
+            if let Some(address) = address {
+                entry = entry.with_bind_address(address);
+            }
+            push_unique(&mut entries, entry);
+        }
+    }
+
+    entries
+}
+
+/// Parses the process column `users:(("name",pid=1,fd=3),("name",pid=2,fd=3))`.
+fn parse_ss_users(field: &str) -> Vec<(u32, String)> {
+    let Some(users) = field.strip_prefix("users:(") else {
+        return Vec::new();
+    };
+
+    users
+        .split("),(")
+        .filter_map(|user| {
+            let user = user.trim_start_matches('(').trim_end_matches(')');
+            let (quoted_name, rest) = user.rsplit_once("\",")?;
+            let name = quoted_name.trim_start_matches('"');
+            let pid = rest
+                .split(',')
+                .find_map(|attribute| attribute.strip_prefix("pid="))?
+                .parse()
+                .ok()?;
+            Some((pid, name.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SS_OUTPUT: &str = "\
+udp UNCONN 0      0      127.0.0.53%lo:53        0.0.0.0:*    users:((\"systemd-resolve\",pid=601,fd=13))
+udp UNCONN 0      0                  *:8125            *:*    users:((\"statsd\",pid=900,fd=4))
+tcp LISTEN 0      4096   127.0.0.53%lo:53        0.0.0.0:*    users:((\"systemd-resolve\",pid=601,fd=14))
+tcp LISTEN 0      511          0.0.0.0:3000      0.0.0.0:*    users:((\"node\",pid=1234,fd=20),(\"node\",pid=1235,fd=20))
+tcp LISTEN 0      128             [::]:3000         [::]:*    users:((\"node\",pid=1234,fd=21))
+tcp LISTEN 0      128          0.0.0.0:22        0.0.0.0:*
+tcp LISTEN 0      128   [::ffff:127.0.0.1]:8080        *:*    users:((\"java\",pid=4321,fd=7))
+";
+
+    #[test]
+    fn test_parse_ss_output() {
+        let entries = parse_ss_output(SS_OUTPUT);
+
+        let summary: Vec<(u16, Protocol, String, u32, &str)> = entries
+            .iter()
+            .map(|e| {
+                let address = e.address_display();
+                (
+                    e.port,
+                    e.protocol,
+                    address,
+                    e.process.pid,
+                    e.process.name.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    53,
+                    Protocol::Udp,
+                    "127.0.0.53".into(),
+                    601,
+                    "systemd-resolve"
+                ),
+                (8125, Protocol::Udp, "::".into(), 900, "statsd"),
+                (
+                    53,
+                    Protocol::Tcp,
+                    "127.0.0.53".into(),
+                    601,
+                    "systemd-resolve"
+                ),
+                (3000, Protocol::Tcp, "0.0.0.0".into(), 1234, "node"),
+                (3000, Protocol::Tcp, "0.0.0.0".into(), 1235, "node"),
+                (3000, Protocol::Tcp, "::".into(), 1234, "node"),
+                (8080, Protocol::Tcp, "::ffff:127.0.0.1".into(), 4321, "java"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_ss_users_with_comma_in_name() {
+        let users = parse_ss_users("users:((\"web,worker\",pid=77,fd=3))");
+        assert_eq!(users, vec![(77, "web,worker".to_string())]);
+    }
+
+    #[test]
+    fn test_parse_ss_users_missing() {
+        assert!(parse_ss_users("").is_empty());
+    }
+}
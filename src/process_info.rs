@@ -2,91 +2,316 @@
 //!
 //! This module provides functions to get detailed process information
 //! on macOS using system commands and the libproc library.
+//! Details for all processes are fetched in one batch so that the cost does
+//! not grow with the number of listening ports. On Linux they are read
+//! directly from the proc filesystem instead.
+//!
+//! The working directory of each process is mapped to the project it
+//! belongs to by [`project`].
+
+mod procfs;
+mod project;
 
 use crate::error::{PortsError, Result};
 use crate::models::ProcessInfo;
-use chrono::{DateTime, TimeZone, Utc};
+use chrono::{DateTime, Local, LocalResult, TimeZone, Utc};
+use project::{Project, ProjectFinder};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
-/// Enriches a ProcessInfo with additional details from the system.
-///
-/// Attempts to retrieve:
-/// - Full command line
-/// - Process start time
-/// - Running user
-///
-/// If some information cannot be retrieved (e.g., due to permissions),
-/// the function will still succeed with partial information.
-pub fn enrich_process_info(process: &mut ProcessInfo) -> Result<()> {
-    // Get command line using ps
-    if let Ok(command) = get_command_line(process.pid) {
-        process.command = Some(command);
-    }
-
-    // Get process start time using ps
-    if let Ok(started_at) = get_start_time(process.pid) {
-        let now = Utc::now();
-        if started_at <= now {
-            let elapsed_seconds = (now - started_at).num_seconds();
-            if elapsed_seconds >= 0 {
-                process.elapsed = Some(std::time::Duration::from_secs(elapsed_seconds as u64));
+/// Details about a process gathered by [`lookup_process_details`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProcessDetails {
+    /// Full command line
+    pub command: Option<String>,
+    /// Command line arguments, including the program as `argv[0]`
+    pub argv: Vec<String>,
+    /// Path of the running executable
+    pub exe: Option<PathBuf>,
+    /// Process start time
+    pub started_at: Option<DateTime<Utc>>,
+    /// User running the process
+    pub user: Option<String>,
+    /// Numeric user ID of the process
+    pub uid: Option<u32>,
+    /// Parent process ID
+    pub ppid: Option<u32>,
+    /// Current working directory
+    pub cwd: Option<PathBuf>,
+    /// Project containing the working directory
+    pub project: Option<Project>,
+}
+
+impl ProcessDetails {
+    /// Copies the details into a ProcessInfo and computes the elapsed time.
+    pub fn apply_to(&self, process: &mut ProcessInfo) {
+        if let Some(command) = &self.command {
+            process.command = Some(command.clone());
+        }
+
+        if !self.argv.is_empty() {
+            process.argv = self.argv.clone();
+        }
+
+        if let Some(exe) = &self.exe {
+            process.exe = Some(exe.clone());
+        }
+
+        if let Some(started_at) = self.started_at {
+            let now = Utc::now();
+            if started_at <= now {
+                let elapsed_seconds = (now - started_at).num_seconds();
+                if elapsed_seconds >= 0 {
+                    process.elapsed = Some(std::time::Duration::from_secs(elapsed_seconds as u64));
+                }
             }
+            process.started_at = Some(started_at);
+        }
+
+        if let Some(user) = &self.user {
+            process.user = Some(user.clone());
+        }
+
+        // The process uid wins over the uid of the socket, which is the owner
+        // at bind time and stays root after a daemon drops its privileges
+        if let Some(uid) = self.uid {
+            process.uid = Some(uid);
+        }
+
+        if let Some(ppid) = self.ppid {
+            process.ppid = Some(ppid);
+        }
+
+        if let Some(cwd) = &self.cwd {
+            process.cwd = Some(cwd.clone());
         }
-        process.started_at = Some(started_at);
+
+        if let Some(project) = &self.project {
+            process.project_name = Some(project.name.clone());
+            process.project_dir = Some(project.dir.clone());
+        }
+    }
+}
+
+/// Looks up details for several processes at once.
+///
+/// Retrieves the full command line, start time, running user and working
+/// directory of every PID, from the proc filesystem on Linux and with a
+/// single `ps` and `lsof` call elsewhere, then finds the project of each
+/// working directory. PIDs that no longer exist are missing from the
+/// returned map; fields that cannot be read are left as `None`.
+pub fn lookup_process_details(pids: &[u32]) -> Result<HashMap<u32, ProcessDetails>> {
+    let mut pids = pids.to_vec();
+    pids.sort_unstable();
+    pids.dedup();
+    if pids.is_empty() {
+        return Ok(HashMap::new());
     }
 
-    // Get user using ps
-    if let Ok(user) = get_user(process.pid) {
-        process.user = Some(user);
+    let proc_root = Path::new(procfs::PROC_ROOT);
+    let mut details = if cfg!(target_os = "linux") && proc_root.join("self/stat").exists() {
+        procfs::read_process_details(
+            proc_root,
+            Path::new(procfs::PASSWD_PATH),
+            procfs::clock_ticks_per_second(),
+            &pids,
+        )
+    } else {
+        lookup_process_details_with_ps(&pids)?
+    };
+
+    let mut projects = ProjectFinder::new();
+    for details in details.values_mut() {
+        details.project = details.cwd.as_deref().and_then(|cwd| projects.find(cwd));
     }
 
-    Ok(())
+    Ok(details)
 }
 
-/// Gets the full command line for a process.
-fn get_command_line(pid: u32) -> Result<String> {
+/// Returns the direct children of a process as `(pid, name)` pairs, in PID
+/// order.
+///
+/// Children are found by their parent PID, from the proc filesystem on
+/// Linux and with `ps` elsewhere. Failures yield an empty list, since the
+/// children are only shown for information.
+pub fn child_processes(pid: u32) -> Vec<(u32, String)> {
+    let proc_root = Path::new(procfs::PROC_ROOT);
+    let mut children = if cfg!(target_os = "linux") && proc_root.join("self/stat").exists() {
+        procfs::child_processes(proc_root, pid)
+    } else {
+        Command::new("ps")
+            .args(["-A", "-o", "pid=,ppid=,comm="])
+            .output()
+            .map(|output| parse_ps_children(&String::from_utf8_lossy(&output.stdout), pid))
+            .unwrap_or_default()
+    };
+
+    children.sort_unstable();
+    children
+}
+
+/// Parses `ps -o pid=,ppid=,comm=` output and returns the processes whose
+/// parent is `parent`.
+fn parse_ps_children(output: &str, parent: u32) -> Vec<(u32, String)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (pid, rest) = split_field(line)?;
+            let (ppid, comm) = split_field(rest)?;
+            if ppid.parse::<u32>().ok()? != parent {
+                return None;
+            }
+
+            // comm is the executable path on macOS
+            let comm = comm.trim();
+            let name = comm.rsplit('/').next().unwrap_or(comm);
+            Some((pid.parse().ok()?, name.to_string()))
+        })
+        .collect()
+}
+
+/// Looks up details for several processes with a single `ps` call.
+fn lookup_process_details_with_ps(pids: &[u32]) -> Result<HashMap<u32, ProcessDetails>> {
+    let pid_list: Vec<String> = pids.iter().map(u32::to_string).collect();
     let output = Command::new("ps")
-        .args(["-p", &pid.to_string(), "-o", "command="])
+        .args([
+            "-o",
+            "pid=,ppid=,uid=,user=,lstart=,command=",
+            "-p",
+            &pid_list.join(","),
+        ])
+        // lstart uses localized month names otherwise
+        .env("LC_ALL", "C")
         .output()
         .map_err(|e| PortsError::system_error(format!("Failed to execute ps: {}", e)))?;
 
-    if !output.status.success() {
-        return Err(PortsError::process_not_found(pid));
+    // ps exits with 1 when none of the PIDs exist any more
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() && !stdout.trim().is_empty() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(PortsError::system_error(format!(
+            "ps failed: {}",
+            stderr.trim()
+        )));
     }
 
-    let command = String::from_utf8_lossy(&output.stdout).trim().to_string();
-    if command.is_empty() {
-        return Err(PortsError::process_not_found(pid));
+    let mut details = parse_ps_details(&stdout, &Local);
+    let mut cwds = working_directories(&pid_list);
+    for (pid, details) in &mut details {
+        details.exe = executable_path(*pid);
+        details.cwd = cwds.remove(pid);
     }
 
-    Ok(command)
+    Ok(details)
 }
 
-/// Gets the start time for a process.
-fn get_start_time(pid: u32) -> Result<DateTime<Utc>> {
-    // Use ps with lstart format for full start time
-    let output = Command::new("ps")
-        .args(["-p", &pid.to_string(), "-o", "lstart="])
-        .output()
-        .map_err(|e| PortsError::system_error(format!("Failed to execute ps: {}", e)))?;
+/// Returns the working directories of several processes with a single
+/// `lsof` call.
+///
+/// Working directories are optional information, so processes whose
+/// directory cannot be read (e.g. those of other users) are simply
+/// missing, and a failing `lsof` yields an empty map.
+fn working_directories(pid_list: &[String]) -> HashMap<u32, PathBuf> {
+    let output = Command::new("lsof")
+        .args(["-a", "-d", "cwd", "-Fn", "-p", &pid_list.join(",")])
+        .output();
+
+    match output {
+        Ok(output) => parse_lsof_cwd(&String::from_utf8_lossy(&output.stdout)),
+        Err(_) => HashMap::new(),
+    }
+}
 
-    if !output.status.success() {
-        return Err(PortsError::process_not_found(pid));
+/// Parses `lsof -d cwd -Fn` output.
+///
+/// Each process is reported as a `p<PID>` line followed by its file
+/// descriptor (`fcwd`) and a `n<path>` line.
+fn parse_lsof_cwd(output: &str) -> HashMap<u32, PathBuf> {
+    let mut cwds = HashMap::new();
+    let mut pid = None;
+
+    for line in output.lines() {
+        if let Some(value) = line.strip_prefix('p') {
+            pid = value.parse().ok();
+        } else if let (Some(path), Some(pid)) = (line.strip_prefix('n'), pid) {
+            cwds.insert(pid, PathBuf::from(path));
+        }
     }
 
-    let lstart = String::from_utf8_lossy(&output.stdout).trim().to_string();
-    if lstart.is_empty() {
-        return Err(PortsError::process_not_found(pid));
+    cwds
+}
+
+/// Returns the path of the executable a process is running.
+fn executable_path(pid: u32) -> Option<PathBuf> {
+    let pid = i32::try_from(pid).ok()?;
+    libproc::proc_pid::pidpath(pid).ok().map(PathBuf::from)
+}
+
+/// Parses `ps -o pid=,ppid=,uid=,user=,lstart=,command=` output.
+///
+/// Each line has the form
+/// `1234 1 501 alice Wed Jan  1 12:34:56 2025 node server.js`: the PID,
+/// the parent PID, the UID, the user, five lstart fields and the command line, which
+/// keeps its original spacing. Start times are read in `timezone`.
+fn parse_ps_details<Tz: TimeZone>(output: &str, timezone: &Tz) -> HashMap<u32, ProcessDetails> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (pid, rest) = split_field(line)?;
+            let pid = pid.parse().ok()?;
+            let (ppid, rest) = split_field(rest)?;
+            let ppid = ppid.parse().ok()?;
+            let (uid, rest) = split_field(rest)?;
+            let uid = uid.parse().ok()?;
+            let (user, mut rest) = split_field(rest)?;
+
+            let mut lstart = Vec::with_capacity(5);
+            for _ in 0..5 {
+                let (field, remainder) = split_field(rest)?;
+                lstart.push(field);
+                rest = remainder;
+            }
+
+            let command = rest.trim();
+            let details = ProcessDetails {
+                command: (!command.is_empty()).then(|| command.to_string()),
+                // ps joins the arguments with spaces, so this is only an
+                // approximation for arguments that contain spaces
+                argv: command.split_whitespace().map(str::to_string).collect(),
+                started_at: parse_lstart(&lstart.join(" "), timezone).ok(),
+                user: Some(user.to_string()),
+                uid: Some(uid),
+                ppid: Some(ppid),
+                ..ProcessDetails::default()
+            };
+            Some((pid, details))
+        })
+        .collect()
+}
+
+/// Splits the first whitespace-separated field off `text`.
+///
+/// Returns the field and the remainder, which still starts with the
+/// separating whitespace.
+fn split_field(text: &str) -> Option<(&str, &str)> {
+    let text = text.trim_start();
+    if text.is_empty() {
+        return None;
     }
 
-    // Parse lstart format: "Mon Jan  2 15:04:05 2006"
-    parse_lstart(&lstart)
+    let end = text.find(char::is_whitespace).unwrap_or(text.len());
+    Some(text.split_at(end))
 }
 
 /// Parses the lstart format from ps.
 ///
 /// Format: "Mon Jan  2 15:04:05 2006" (day of week, month, day, time, year)
-fn parse_lstart(lstart: &str) -> Result<DateTime<Utc>> {
+///
+/// ps prints the start time in the local timezone (`TZ` or the system
+/// default), so callers pass [`Local`] and the time is converted to UTC. A
+/// time that occurs twice when DST ends resolves to the earlier instant.
+fn parse_lstart<Tz: TimeZone>(lstart: &str, timezone: &Tz) -> Result<DateTime<Utc>> {
     // Try parsing with chrono
     // The format is: "Wed Jan  1 12:34:56 2025"
     let parts: Vec<&str> = lstart.split_whitespace().collect();
@@ -145,39 +370,33 @@ fn parse_lstart(lstart: &str) -> Result<DateTime<Utc>> {
         .parse()
         .map_err(|_| PortsError::system_error(format!("Invalid year: {}", parts[4])))?;
 
-    Utc.with_ymd_and_hms(year, month, day, hour, minute, second)
-        .single()
-        .ok_or_else(|| PortsError::system_error(format!("Invalid date: {}", lstart)))
-}
-
-/// Gets the user running a process.
-fn get_user(pid: u32) -> Result<String> {
-    let output = Command::new("ps")
-        .args(["-p", &pid.to_string(), "-o", "user="])
-        .output()
-        .map_err(|e| PortsError::system_error(format!("Failed to execute ps: {}", e)))?;
-
-    if !output.status.success() {
-        return Err(PortsError::process_not_found(pid));
-    }
-
-    let user = String::from_utf8_lossy(&output.stdout).trim().to_string();
-    if user.is_empty() {
-        return Err(PortsError::process_not_found(pid));
-    }
+    let started_at = match timezone.with_ymd_and_hms(year, month, day, hour, minute, second) {
+        LocalResult::Single(local) => Some(local.with_timezone(&Utc)),
+        // The pair is not ordered by instant, so compare in UTC
+        LocalResult::Ambiguous(first, second) => {
+            Some(first.with_timezone(&Utc).min(second.with_timezone(&Utc)))
+        }
+        LocalResult::None => None,
+    };
 
-    Ok(user)
+    started_at.ok_or_else(|| PortsError::system_error(format!("Invalid date: {}", lstart)))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use chrono::{Datelike, Timelike};
+    use chrono_tz::{America::New_York, Asia::Tokyo, Australia::Sydney};
+
+    fn utc(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, hour, minute, 0)
+            .unwrap()
+    }
 
     #[test]
     fn test_parse_lstart() {
         let lstart = "Wed Jan  1 12:34:56 2025";
-        let result = parse_lstart(lstart);
+        let result = parse_lstart(lstart, &Utc);
         assert!(result.is_ok());
 
         let dt = result.unwrap();
@@ -188,4 +407,310 @@ mod tests {
         assert_eq!(dt.second(), 56);
         assert_eq!(dt.year(), 2025);
     }
+
+    #[test]
+    fn test_parse_lstart_tokyo() {
+        let started_at = parse_lstart("Wed Jan  1 09:00:00 2025", &Tokyo);
+        assert_eq!(started_at.unwrap(), utc(2025, 1, 1, 0, 0));
+    }
+
+    #[test]
+    fn test_parse_lstart_dst() {
+        // Standard time (UTC-5) and daylight saving time (UTC-4)
+        let winter = parse_lstart("Wed Jan 15 08:00:00 2025", &New_York);
+        assert_eq!(winter.unwrap(), utc(2025, 1, 15, 13, 0));
+        let summer = parse_lstart("Tue Jul 15 08:00:00 2025", &New_York);
+        assert_eq!(summer.unwrap(), utc(2025, 7, 15, 12, 0));
+
+        // 01:30 occurs twice on the night DST ends
+        let repeated = parse_lstart("Sun Nov  2 01:30:00 2025", &New_York);
+        assert_eq!(repeated.unwrap(), utc(2025, 11, 2, 5, 30));
+
+        // 02:30 does not exist on the night DST starts
+        let skipped = parse_lstart("Sun Mar  9 02:30:00 2025", &New_York);
+        assert!(skipped.is_err());
+    }
+
+    #[test]
+    fn test_parse_lstart_southern_hemisphere_dst() {
+        // Sydney observes DST (UTC+11) during the southern summer
+        let january = parse_lstart("Wed Jan  1 11:00:00 2025", &Sydney);
+        assert_eq!(january.unwrap(), utc(2025, 1, 1, 0, 0));
+        let june = parse_lstart("Sun Jun  1 10:00:00 2025", &Sydney);
+        assert_eq!(june.unwrap(), utc(2025, 6, 1, 0, 0));
+    }
+
+    #[test]
+    fn test_parse_ps_details() {
+        let output = "\
+  364     1   216 _postgres Mon Dec 30 09:00:00 2024 /usr/local/bin/postgres -D  /var/db/pg
+12345 12001   501 alice     Wed Jan  1 12:34:56 2025 node server.js
+  777     1   502 bob       Thu Jan  2 08:00:00 2025
+";
+        let details = parse_ps_details(output, &Utc);
+
+        assert_eq!(details.len(), 3);
+
+        let postgres = &details[&364];
+        assert_eq!(postgres.user.as_deref(), Some("_postgres"));
+        assert_eq!(postgres.uid, Some(216));
+        assert_eq!(postgres.ppid, Some(1));
+        // Spacing inside the command line is preserved
+        assert_eq!(
+            postgres.command.as_deref(),
+            Some("/usr/local/bin/postgres -D  /var/db/pg")
+        );
+
+        let node = &details[&12345];
+        assert_eq!(node.uid, Some(501));
+        assert_eq!(node.command.as_deref(), Some("node server.js"));
+        assert_eq!(
+            node.started_at,
+            Utc.with_ymd_and_hms(2025, 1, 1, 12, 34, 56).single()
+        );
+
+        assert_eq!(details[&777].command, None);
+    }
+
+    #[test]
+    fn test_parse_ps_details_skips_malformed_lines() {
+        assert!(parse_ps_details("abc 1 0 root Wed Jan  1 12:34:56 2025 x\n", &Utc).is_empty());
+        // A user name where the UID belongs
+        assert!(parse_ps_details("42 1 root Wed Jan  1 12:34:56 2025 x\n", &Utc).is_empty());
+        assert!(parse_ps_details("42 1 0 root Wed Jan\n", &Utc).is_empty());
+        assert!(parse_ps_details("", &Utc).is_empty());
+    }
+
+    /// `--user` and `--where user = ...` match UIDs read by ps as well as
+    /// those read from procfs.
+    #[test]
+    fn test_ps_details_filtered_by_uid() {
+        use crate::filter::EntryFilter;
+        use crate::models::{PortEntry, Protocol};
+
+        let output = "\
+  364     1   216 _postgres Mon Dec 30 09:00:00 2024 postgres -D /var/db/pg
+12345 12001   501 alice     Wed Jan  1 12:34:56 2025 node server.js
+";
+        let details = parse_ps_details(output, &Utc);
+        let entries: Vec<PortEntry> = [(5432, 364), (3000, 12345)]
+            .into_iter()
+            .map(|(port, pid)| {
+                let mut process = ProcessInfo::new(pid, "test");
+                details[&pid].apply_to(&mut process);
+                PortEntry::new(port, Protocol::Tcp, process)
+            })
+            .collect();
+
+        let ports = |filter: EntryFilter| {
+            let mut entries = entries.clone();
+            filter.apply(&mut entries);
+            entries.iter().map(|e| e.port).collect::<Vec<_>>()
+        };
+        let by_user = |user: &str| EntryFilter {
+            user: Some(user.to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(ports(by_user("501")), vec![3000]);
+        assert_eq!(ports(by_user("_postgres")), vec![5432]);
+        assert_eq!(
+            ports(EntryFilter {
+                query: Some("user = 216".parse().unwrap()),
+                ..Default::default()
+            }),
+            vec![5432]
+        );
+    }
+
+    #[test]
+    fn test_parse_ps_children() {
+        let output = "\
+    1     0 /sbin/launchd
+  500     1 /usr/local/bin/node
+  501   500 /usr/local/bin/esbuild
+  502   500 node
+  503   501 sh
+";
+        assert_eq!(
+            parse_ps_children(output, 500),
+            vec![(501, "esbuild".to_string()), (502, "node".to_string())]
+        );
+        assert!(parse_ps_children(output, 502).is_empty());
+    }
+
+    #[test]
+    fn test_child_processes() {
+        let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+        let children = child_processes(std::process::id());
+        child.kill().unwrap();
+        child.wait().unwrap();
+
+        assert!(children.contains(&(child.id(), "sleep".to_string())));
+    }
+
+    #[test]
+    fn test_parse_lsof_cwd() {
+        let output = "p123\nfcwd\nn/Users/alice/src/web\np456\nfcwd\nn/\np789\n";
+        let cwds = parse_lsof_cwd(output);
+
+        assert_eq!(cwds.len(), 2);
+        assert_eq!(cwds[&123], PathBuf::from("/Users/alice/src/web"));
+        assert_eq!(cwds[&456], PathBuf::from("/"));
+    }
+
+    #[test]
+    fn test_apply_to() {
+        let details = ProcessDetails {
+            command: Some("node server.js".to_string()),
+            started_at: Some(Utc::now() - chrono::Duration::seconds(90)),
+            user: Some("alice".to_string()),
+            ..ProcessDetails::default()
+        };
+        let mut process = ProcessInfo::new(1, "node");
+
+        details.apply_to(&mut process);
+
+        assert_eq!(process.command.as_deref(), Some("node server.js"));
+        assert_eq!(process.user.as_deref(), Some("alice"));
+        assert!(process.elapsed.unwrap().as_secs() >= 90);
+    }
+
+    #[test]
+    fn test_apply_to_replaces_socket_uid() {
+        // Bound as root, then switched to www-data
+        let mut process = ProcessInfo::new(1, "nginx");
+        process.uid = Some(0);
+
+        ProcessDetails {
+            uid: Some(33),
+            user: Some("www-data".to_string()),
+            ..ProcessDetails::default()
+        }
+        .apply_to(&mut process);
+        assert_eq!(process.uid, Some(33));
+        assert_eq!(process.user.as_deref(), Some("www-data"));
+
+        // Without a uid in the details the socket uid is kept
+        let mut unread = ProcessInfo::new(2, "nginx");
+        unread.uid = Some(0);
+        ProcessDetails::default().apply_to(&mut unread);
+        assert_eq!(unread.uid, Some(0));
+    }
+
+    #[test]
+    fn test_lookup_process_details_includes_self() {
+        let pid = std::process::id();
+        let details = lookup_process_details(&[pid, pid]).unwrap();
+
+        let own = details.get(&pid).expect("own process not found");
+        assert!(own.command.is_some());
+        assert!(!own.argv.is_empty());
+        assert_eq!(own.exe, std::env::current_exe().ok());
+        assert!(own.started_at.is_some());
+    }
+
+    #[test]
+    fn test_lookup_process_details_with_ps_includes_self() {
+        let pid = std::process::id();
+        let details = lookup_process_details_with_ps(&[pid]).unwrap();
+
+        let own = details.get(&pid).expect("own process not found");
+        // SAFETY: geteuid has no preconditions and cannot fail
+        assert_eq!(own.uid, Some(unsafe { libc::geteuid() }));
+    }
+
+    /// ps and procfs must agree on the start time whatever timezone ps
+    /// prints it in.
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_ps_start_time_matches_procfs() {
+        use chrono::FixedOffset;
+
+        let pid = std::process::id();
+        let from_procfs = lookup_process_details(&[pid]).unwrap()[&pid]
+            .started_at
+            .unwrap();
+        let assert_matches = |from_ps: DateTime<Utc>, tz: &str| {
+            let difference = (from_ps - from_procfs).num_seconds();
+            assert!(difference.abs() <= 1, "{}: off by {}s", tz, difference);
+        };
+
+        let details = lookup_process_details_with_ps(&[pid]).unwrap();
+        assert_matches(details[&pid].started_at.unwrap(), "local");
+
+        // POSIX TZ strings, so ps does not need the timezone database
+        for (tz, offset) in [("UTC0", 0), ("JST-9", 9 * 3600), ("<-05>5", -5 * 3600)] {
+            let output = Command::new("ps")
+                .args(["-o", "lstart=", "-p", &pid.to_string()])
+                .env("LC_ALL", "C")
+                .env("TZ", tz)
+                .output()
+                .unwrap();
+            let lstart = String::from_utf8_lossy(&output.stdout);
+            let timezone = FixedOffset::east_opt(offset).unwrap();
+            assert_matches(parse_lstart(lstart.trim(), &timezone).unwrap(), tz);
+        }
+    }
+
+    #[test]
+    fn test_lookup_process_details_empty() {
+        assert!(lookup_process_details(&[]).unwrap().is_empty());
+    }
+
+    /// Compares one `ps` call per field and PID with the batched `ps` call
+    /// and the default (procfs on Linux) lookup.
+    ///
+    /// Run with `cargo test --release -- --ignored --nocapture bench_`.
+    #[test]
+    #[ignore = "benchmark"]
+    fn bench_batched_lookup() {
+        use std::process::{Command, Stdio};
+        use std::time::Instant;
+
+        const PROCESS_COUNT: usize = 50;
+
+        let mut children: Vec<_> = (0..PROCESS_COUNT)
+            .map(|_| {
+                Command::new("sleep")
+                    .arg("60")
+                    .stdout(Stdio::null())
+                    .spawn()
+                    .unwrap()
+            })
+            .collect();
+        let pids: Vec<u32> = children.iter().map(|c| c.id()).collect();
+
+        // The previous implementation ran three ps processes per PID
+        let start = Instant::now();
+        for pid in &pids {
+            for field in ["command=", "lstart=", "user="] {
+                Command::new("ps")
+                    .args(["-p", &pid.to_string(), "-o", field])
+                    .output()
+                    .unwrap();
+            }
+        }
+        let per_pid = start.elapsed();
+
+        let start = Instant::now();
+        let details = lookup_process_details_with_ps(&pids).unwrap();
+        let batched = start.elapsed();
+
+        let start = Instant::now();
+        lookup_process_details(&pids).unwrap();
+        let procfs = start.elapsed();
+
+        for child in &mut children {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+
+        println!(
+            "{} processes: per-PID ps {:?}, batched ps {:?}, default lookup {:?}",
+            PROCESS_COUNT, per_pid, batched, procfs
+        );
+        assert_eq!(details.len(), PROCESS_COUNT);
+        assert!(batched < per_pid);
+    }
 }
@@ -0,0 +1,422 @@
+//! Linux procfs process details.
+//!
+//! Reads the command line, start time and owner of processes directly from
+//! `/proc/<pid>/{cmdline,stat,status}` instead of running `ps`. Unlike
+//! `ps`, which flattens the command line into one string, the NUL-separated
+//! argument vector is kept intact, and the executable path and working
+//! directory are read from the `/proc/<pid>/exe` and `/proc/<pid>/cwd`
+//! links.
+
+use super::ProcessDetails;
+use chrono::{DateTime, TimeZone, Utc};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Default mount point of the proc filesystem.
+pub(super) const PROC_ROOT: &str = "/proc";
+
+/// Default location of the user database.
+pub(super) const PASSWD_PATH: &str = "/etc/passwd";
+
+/// Index of `starttime` among the `/proc/<pid>/stat` fields that follow
+/// the parenthesized command name (field 22 overall, counting from 1).
+const STAT_STARTTIME_INDEX: usize = 19;
+
+/// Returns the kernel clock tick rate used for `/proc/<pid>/stat` times.
+pub(super) fn clock_ticks_per_second() -> u64 {
+    // SAFETY: sysconf has no preconditions
+    let ticks = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+    if ticks > 0 {
+        ticks as u64
+    } else {
+        100
+    }
+}
+
+/// Reads details for several processes from a proc filesystem.
+///
+/// `proc_root` and `passwd_path` are parameters so that tests can point
+/// them at a fake tree. Processes that exited or cannot be read are
+/// missing from the returned map.
+pub(super) fn read_process_details(
+    proc_root: &Path,
+    passwd_path: &Path,
+    clock_ticks: u64,
+    pids: &[u32],
+) -> HashMap<u32, ProcessDetails> {
+    let boot_time = read_boot_time(proc_root);
+    let users = fs::read_to_string(passwd_path)
+        .map(|content| parse_passwd(&content))
+        .unwrap_or_default();
+
+    pids.iter()
+        .filter_map(|&pid| {
+            let process_dir = proc_root.join(pid.to_string());
+            // The status file exists for every live process, including
+            // kernel threads and zombies whose cmdline is empty
+            let status = fs::read_to_string(process_dir.join("status")).ok()?;
+
+            let argv = fs::read(process_dir.join("cmdline"))
+                .map(|content| parse_cmdline(&content))
+                .unwrap_or_default();
+
+            let exe = read_link(&process_dir, "exe");
+            let cwd = read_link(&process_dir, "cwd");
+
+            let started_at = fs::read_to_string(process_dir.join("stat"))
+                .ok()
+                .and_then(|stat| parse_stat_starttime(&stat))
+                .zip(boot_time)
+                .and_then(|(starttime, boot_time)| {
+                    start_time_from_ticks(boot_time, starttime, clock_ticks)
+                });
+
+            let uid = parse_status_uid(&status);
+            let ppid = parse_status_ppid(&status);
+            let user = uid.map(|uid| users.get(&uid).cloned().unwrap_or_else(|| uid.to_string()));
+
+            let details = ProcessDetails {
+                command: (!argv.is_empty()).then(|| argv.join(" ")),
+                argv,
+                exe,
+                started_at,
+                user,
+                uid,
+                ppid,
+                cwd,
+                project: None,
+            };
+            Some((pid, details))
+        })
+        .collect()
+}
+
+/// Splits `/proc/<pid>/cmdline` into its NUL-separated arguments.
+fn parse_cmdline(content: &[u8]) -> Vec<String> {
+    if content.is_empty() {
+        return Vec::new();
+    }
+
+    content
+        .strip_suffix(b"\0")
+        .unwrap_or(content)
+        .split(|byte| *byte == 0)
+        .map(|argument| String::from_utf8_lossy(argument).into_owned())
+        .collect()
+}
+
+/// Reads the `exe` or `cwd` link of a process directory.
+///
+/// The links are only readable for processes of the current user (or by
+/// root). The kernel appends ` (deleted)` when the target was removed
+/// after the process started, e.g. a binary replaced by a rebuild.
+fn read_link(process_dir: &Path, name: &str) -> Option<PathBuf> {
+    let target = fs::read_link(process_dir.join(name)).ok()?;
+    match target.to_str().and_then(|t| t.strip_suffix(" (deleted)")) {
+        Some(path) => Some(PathBuf::from(path)),
+        None => Some(target),
+    }
+}
+
+/// Returns the processes whose parent is `parent` as `(pid, name)` pairs.
+pub(super) fn child_processes(proc_root: &Path, parent: u32) -> Vec<(u32, String)> {
+    let Ok(dir) = fs::read_dir(proc_root) else {
+        return Vec::new();
+    };
+
+    dir.filter_map(|entry| {
+        let pid: u32 = entry.ok()?.file_name().to_str()?.parse().ok()?;
+        let status = fs::read_to_string(proc_root.join(pid.to_string()).join("status")).ok()?;
+        if parse_status_ppid(&status)? != parent {
+            return None;
+        }
+
+        let name = status
+            .lines()
+            .find_map(|line| line.strip_prefix("Name:"))?
+            .trim();
+        Some((pid, name.to_string()))
+    })
+    .collect()
+}
+
+/// Returns the `starttime` field (clock ticks after boot) of
+/// `/proc/<pid>/stat`.
+///
+/// The command name in the second field may itself contain spaces and
+/// parentheses, so fields are counted from the last `)`.
+fn parse_stat_starttime(stat: &str) -> Option<u64> {
+    let (_, fields) = stat.rsplit_once(')')?;
+    fields
+        .split_whitespace()
+        .nth(STAT_STARTTIME_INDEX)?
+        .parse()
+        .ok()
+}
+
+/// Returns the effective UID from `/proc/<pid>/status`.
+///
+/// The `Uid:` line lists the real, effective, saved and filesystem UIDs;
+/// like `ps -o user`, the effective UID is used.
+fn parse_status_uid(status: &str) -> Option<u32> {
+    let uids = status.lines().find_map(|line| line.strip_prefix("Uid:"))?;
+    uids.split_whitespace().nth(1)?.parse().ok()
+}
+
+/// Returns the parent PID from `/proc/<pid>/status`.
+fn parse_status_ppid(status: &str) -> Option<u32> {
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("PPid:"))?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Reads the system boot time (`btime`, seconds since the epoch) from
+/// `/proc/stat`.
+fn read_boot_time(proc_root: &Path) -> Option<i64> {
+    let stat = fs::read_to_string(proc_root.join("stat")).ok()?;
+    stat.lines()
+        .find_map(|line| line.strip_prefix("btime "))?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Converts a start time in clock ticks after boot to a timestamp.
+fn start_time_from_ticks(
+    boot_time: i64,
+    starttime: u64,
+    clock_ticks: u64,
+) -> Option<DateTime<Utc>> {
+    let seconds = i64::try_from(starttime / clock_ticks.max(1)).ok()?;
+    Utc.timestamp_opt(boot_time.checked_add(seconds)?, 0)
+        .single()
+}
+
+/// Parses `/etc/passwd` into a UID to user name map.
+///
+/// The first entry wins when several names share a UID, matching the
+/// behaviour of `getpwuid`.
+fn parse_passwd(content: &str) -> HashMap<u32, String> {
+    let mut users = HashMap::new();
+    for line in content.lines() {
+        if line.starts_with('#') {
+            continue;
+        }
+        let mut fields = line.split(':');
+        let (Some(name), Some(_password), Some(uid)) =
+            (fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        if let Ok(uid) = uid.parse() {
+            users.entry(uid).or_insert_with(|| name.to_string());
+        }
+    }
+    users
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    const BOOT_TIME: i64 = 1_735_689_600; // 2025-01-01T00:00:00Z
+
+    const PASSWD: &str = "\
+# comment line
+root:x:0:0:root:/root:/bin/bash
+alice:x:1000:1000:Alice:/home/alice:/bin/zsh
+alias:x:1000:1000:Alias:/home/alice:/bin/zsh
+";
+
+    /// Builds a `/proc/<pid>/stat` line with the given command name and
+    /// starttime.
+    fn stat_line(pid: u32, comm: &str, starttime: u64) -> String {
+        let mut fields = vec!["0"; 50];
+        fields[STAT_STARTTIME_INDEX] = "STARTTIME";
+        format!("{} ({}) S {}", pid, comm, fields[1..].join(" "))
+            .replace("STARTTIME", &starttime.to_string())
+    }
+
+    /// Creates a fake proc tree and passwd file in a temporary directory.
+    fn fake_system() -> (tempfile::TempDir, PathBuf, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let proc_root = dir.path().join("proc");
+        fs::create_dir(&proc_root).unwrap();
+        fs::write(
+            proc_root.join("stat"),
+            format!("cpu  1 2 3 4\nbtime {}\nprocesses 42\n", BOOT_TIME),
+        )
+        .unwrap();
+
+        let passwd = dir.path().join("passwd");
+        fs::write(&passwd, PASSWD).unwrap();
+
+        (dir, proc_root, passwd)
+    }
+
+    fn write_process(
+        proc_root: &Path,
+        pid: u32,
+        cmdline: &[u8],
+        comm: &str,
+        starttime: u64,
+        uid: u32,
+    ) {
+        let dir = proc_root.join(pid.to_string());
+        fs::create_dir(&dir).unwrap();
+        fs::write(dir.join("cmdline"), cmdline).unwrap();
+        fs::write(dir.join("stat"), stat_line(pid, comm, starttime)).unwrap();
+        fs::write(
+            dir.join("status"),
+            format!(
+                "Name:\t{}\nPPid:\t1\nUid:\t{}\t{}\t{}\t{}\nGid:\t0\t0\t0\t0\n",
+                comm, uid, uid, uid, uid
+            ),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_read_process_details() {
+        let (_dir, proc_root, passwd) = fake_system();
+        write_process(
+            &proc_root,
+            1234,
+            b"node\0server.js\0--title\0my app\0",
+            "node",
+            360_000,
+            1000,
+        );
+
+        let details = read_process_details(&proc_root, &passwd, 100, &[1234]);
+        let node = &details[&1234];
+
+        assert_eq!(node.argv, vec!["node", "server.js", "--title", "my app"]);
+        assert_eq!(
+            node.command.as_deref(),
+            Some("node server.js --title my app")
+        );
+        assert_eq!(
+            node.started_at,
+            Utc.timestamp_opt(BOOT_TIME + 3600, 0).single()
+        );
+        assert_eq!(node.user.as_deref(), Some("alice"));
+        assert_eq!(node.uid, Some(1000));
+        assert_eq!(node.ppid, Some(1));
+        assert_eq!(node.exe, None);
+    }
+
+    #[test]
+    fn test_read_process_details_exe_link() {
+        let (dir, proc_root, passwd) = fake_system();
+        write_process(&proc_root, 55, b"./server\0", "server", 0, 1000);
+        let process_dir = proc_root.join("55");
+        std::os::unix::fs::symlink("/srv/app/server (deleted)", process_dir.join("exe")).unwrap();
+        write_process(&proc_root, 56, b"node\0", "node", 0, 1000);
+        let node_exe = dir.path().join("node");
+        std::os::unix::fs::symlink(&node_exe, proc_root.join("56/exe")).unwrap();
+
+        let details = read_process_details(&proc_root, &passwd, 100, &[55, 56]);
+
+        assert_eq!(details[&55].exe, Some(PathBuf::from("/srv/app/server")));
+        assert_eq!(details[&56].exe, Some(node_exe));
+    }
+
+    #[test]
+    fn test_read_process_details_cwd_link() {
+        let (_dir, proc_root, passwd) = fake_system();
+        write_process(&proc_root, 55, b"node ", "node", 0, 1000);
+        std::os::unix::fs::symlink("/home/alice/src/web", proc_root.join("55/cwd")).unwrap();
+        write_process(&proc_root, 56, b"node ", "node", 0, 1000);
+
+        let details = read_process_details(&proc_root, &passwd, 100, &[55, 56]);
+
+        assert_eq!(details[&55].cwd, Some(PathBuf::from("/home/alice/src/web")));
+        assert_eq!(details[&56].cwd, None);
+    }
+
+    #[test]
+    fn test_read_process_details_kernel_thread_and_unknown_uid() {
+        let (_dir, proc_root, passwd) = fake_system();
+        write_process(&proc_root, 2, b"", "kthreadd", 0, 0);
+        write_process(&proc_root, 77, b"/opt/app\0", "app", 100, 4242);
+
+        let details = read_process_details(&proc_root, &passwd, 100, &[2, 77]);
+
+        assert!(details[&2].argv.is_empty());
+        assert_eq!(details[&2].command, None);
+        assert_eq!(details[&2].user.as_deref(), Some("root"));
+        assert_eq!(details[&77].user.as_deref(), Some("4242"));
+    }
+
+    #[test]
+    fn test_read_process_details_skips_missing_processes() {
+        let (_dir, proc_root, passwd) = fake_system();
+        write_process(&proc_root, 10, b"a\0", "a", 0, 0);
+
+        let details = read_process_details(&proc_root, &passwd, 100, &[10, 11]);
+
+        assert_eq!(details.len(), 1);
+        assert!(details.contains_key(&10));
+    }
+
+    #[test]
+    fn test_read_process_details_without_boot_time() {
+        let (_dir, proc_root, passwd) = fake_system();
+        fs::remove_file(proc_root.join("stat")).unwrap();
+        write_process(&proc_root, 10, b"a\0", "a", 100, 0);
+
+        let details = read_process_details(&proc_root, &passwd, 100, &[10]);
+
+        assert_eq!(details[&10].started_at, None);
+        assert_eq!(details[&10].command.as_deref(), Some("a"));
+    }
+
+    #[test]
+    fn test_child_processes() {
+        let (_dir, proc_root, _passwd) = fake_system();
+        write_process(&proc_root, 1234, b"node\0", "node", 1, 1000);
+        write_process(&proc_root, 77, b"bash\0", "bash", 1, 1000);
+        fs::create_dir(proc_root.join("self")).unwrap();
+
+        let mut children = child_processes(&proc_root, 1);
+        children.sort_unstable();
+        assert_eq!(
+            children,
+            vec![(77, "bash".to_string()), (1234, "node".to_string())]
+        );
+        assert!(child_processes(&proc_root, 1234).is_empty());
+    }
+
+    #[test]
+    fn test_parse_stat_starttime_with_parentheses_in_name() {
+        let stat = stat_line(99, "my (weird) name", 4567);
+        assert_eq!(parse_stat_starttime(&stat), Some(4567));
+        assert_eq!(parse_stat_starttime("99 (truncated"), None);
+    }
+
+    #[test]
+    fn test_parse_cmdline() {
+        assert_eq!(parse_cmdline(b"a\0b c\0"), vec!["a", "b c"]);
+        // Processes that rewrite their title may drop the trailing NUL
+        assert_eq!(parse_cmdline(b"nginx: worker"), vec!["nginx: worker"]);
+        assert!(parse_cmdline(b"").is_empty());
+    }
+
+    #[test]
+    fn test_parse_passwd_first_entry_wins() {
+        let users = parse_passwd(PASSWD);
+        assert_eq!(users.get(&0).map(String::as_str), Some("root"));
+        assert_eq!(users.get(&1000).map(String::as_str), Some("alice"));
+    }
+
+    #[test]
+    fn test_clock_ticks_per_second() {
+        assert!(clock_ticks_per_second() > 0);
+    }
+}
@@ -0,0 +1,201 @@
+//! Project detection.
+//!
+//! Finds the project a process belongs to by walking up from its working
+//! directory to the nearest directory containing a project marker
+//! (`package.json`, `Cargo.toml`, `pyproject.toml` or `.git`). The project
+//! name is read from the manifest when it declares one, and is the
+//! directory name otherwise.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Files and directories that mark a project root, in the order their
+/// names are looked up.
+const PROJECT_MARKERS: &[&str] = &["package.json", "Cargo.toml", "pyproject.toml", ".git"];
+
+/// A project root directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Project {
+    /// Name declared in the manifest, or the directory name
+    pub name: String,
+    /// The project root directory
+    pub dir: PathBuf,
+}
+
+/// Finds projects for working directories, remembering the result for
+/// each directory.
+///
+/// Many processes usually share a working directory (e.g. a dev server and
+/// its workers), so each directory is only examined once.
+#[derive(Debug)]
+pub struct ProjectFinder {
+    /// Directories that are never treated as a project root
+    excluded: Vec<PathBuf>,
+    cache: HashMap<PathBuf, Option<Project>>,
+}
+
+impl ProjectFinder {
+    /// Creates a finder that never treats the file system root or the
+    /// user's home directory (often a dotfiles repository) as a project.
+    pub fn new() -> Self {
+        let mut excluded = vec![PathBuf::from("/")];
+        excluded.extend(std::env::var_os("HOME").map(PathBuf::from));
+        Self::with_excluded(excluded)
+    }
+
+    /// Creates a finder with a custom list of excluded directories.
+    pub fn with_excluded(excluded: Vec<PathBuf>) -> Self {
+        Self {
+            excluded,
+            cache: HashMap::new(),
+        }
+    }
+
+    /// Returns the project containing `cwd`, if any.
+    pub fn find(&mut self, cwd: &Path) -> Option<Project> {
+        if let Some(project) = self.cache.get(cwd) {
+            return project.clone();
+        }
+
+        let project = cwd
+            .ancestors()
+            .take_while(|dir| !self.excluded.iter().any(|excluded| excluded == dir))
+            .find(|dir| PROJECT_MARKERS.iter().any(|m| dir.join(m).exists()))
+            .map(|dir| Project {
+                name: manifest_name(dir).unwrap_or_else(|| directory_name(dir)),
+                dir: dir.to_path_buf(),
+            });
+
+        self.cache.insert(cwd.to_path_buf(), project.clone());
+        project
+    }
+}
+
+/// Returns the project name declared in a manifest in `dir`.
+fn manifest_name(dir: &Path) -> Option<String> {
+    let read = |file: &str| fs::read_to_string(dir.join(file)).ok();
+
+    let name = if let Some(content) = read("package.json") {
+        let json: serde_json::Value = serde_json::from_str(&content).ok()?;
+        json.get("name")?.as_str()?.to_string()
+    } else if let Some(content) = read("Cargo.toml") {
+        let toml: toml::Table = content.parse().ok()?;
+        toml_string(&toml, &["package", "name"])?
+    } else if let Some(content) = read("pyproject.toml") {
+        let toml: toml::Table = content.parse().ok()?;
+        toml_string(&toml, &["project", "name"])
+            .or_else(|| toml_string(&toml, &["tool", "poetry", "name"]))?
+    } else {
+        return None;
+    };
+
+    (!name.is_empty()).then_some(name)
+}
+
+/// Looks up a string value by its key path in a TOML table.
+fn toml_string(table: &toml::Table, path: &[&str]) -> Option<String> {
+    let (last, tables) = path.split_last()?;
+    let mut table = table;
+    for key in tables {
+        table = table.get(*key)?.as_table()?;
+    }
+    table.get(*last)?.as_str().map(str::to_string)
+}
+
+/// Returns the last component of a directory path.
+fn directory_name(dir: &Path) -> String {
+    dir.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| dir.display().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(dir: &Path, file: &str, content: &str) {
+        let path = dir.join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    fn finder(root: &Path) -> ProjectFinder {
+        ProjectFinder::with_excluded(vec![root.to_path_buf()])
+    }
+
+    #[test]
+    fn test_manifest_names() {
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path();
+        write(root, "web/package.json", r#"{"name": "@acme/web"}"#);
+        write(root, "api/Cargo.toml", "[package]\nname = \"acme-api\"\n");
+        write(root, "ml/pyproject.toml", "[project]\nname = \"acme-ml\"\n");
+        write(
+            root,
+            "legacy/pyproject.toml",
+            "[tool.poetry]\nname = \"legacy\"\n",
+        );
+        write(root, "ws/Cargo.toml", "[workspace]\nmembers = []\n");
+        fs::create_dir_all(root.join("plain/.git")).unwrap();
+
+        let mut finder = finder(root);
+        let name =
+            |finder: &mut ProjectFinder, dir: &str| finder.find(&root.join(dir)).map(|p| p.name);
+
+        assert_eq!(name(&mut finder, "web").as_deref(), Some("@acme/web"));
+        assert_eq!(name(&mut finder, "api").as_deref(), Some("acme-api"));
+        assert_eq!(name(&mut finder, "ml").as_deref(), Some("acme-ml"));
+        assert_eq!(name(&mut finder, "legacy").as_deref(), Some("legacy"));
+        assert_eq!(name(&mut finder, "ws").as_deref(), Some("ws"));
+        assert_eq!(name(&mut finder, "plain").as_deref(), Some("plain"));
+    }
+
+    #[test]
+    fn test_walks_up_to_nearest_root() {
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path();
+        fs::create_dir_all(root.join("repo/.git")).unwrap();
+        write(root, "repo/packages/ui/package.json", r#"{"name": "ui"}"#);
+        fs::create_dir_all(root.join("repo/packages/ui/src/components")).unwrap();
+        fs::create_dir_all(root.join("repo/docs")).unwrap();
+
+        let mut finder = finder(root);
+
+        let project = finder
+            .find(&root.join("repo/packages/ui/src/components"))
+            .unwrap();
+        assert_eq!(project.name, "ui");
+        assert_eq!(project.dir, root.join("repo/packages/ui"));
+
+        let project = finder.find(&root.join("repo/docs")).unwrap();
+        assert_eq!(project.name, "repo");
+        assert_eq!(project.dir, root.join("repo"));
+    }
+
+    #[test]
+    fn test_no_project() {
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path();
+        fs::create_dir_all(root.join("tmp")).unwrap();
+        // A marker in an excluded directory does not make it a project
+        fs::create_dir_all(root.join(".git")).unwrap();
+
+        let mut finder = finder(root);
+        assert_eq!(finder.find(&root.join("tmp")), None);
+        assert_eq!(finder.find(root), None);
+    }
+
+    #[test]
+    fn test_results_are_cached() {
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path();
+        write(root, "app/package.json", "{}");
+
+        let mut finder = finder(root);
+        assert_eq!(finder.find(&root.join("app")).unwrap().name, "app");
+
+        fs::remove_file(root.join("app/package.json")).unwrap();
+        assert_eq!(finder.find(&root.join("app")).unwrap().name, "app");
+    }
+}
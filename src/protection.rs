@@ -0,0 +1,397 @@
+//! Safeguards against stopping system processes.
+//!
+//! `ports kill` refuses to stop a protected process unless `--force` is
+//! given. A process is protected if it is PID 1 (launchd or systemd), a
+//! well-known system daemon, runs an executable from a system directory,
+//! is a database or broker started directly by the init system, or belongs to
+//! another user when running as root. Users can extend the built-in rules
+//! in `~/.config/ports/protection.toml`:
+//!
+//! ```toml
+//! # Always require --force
+//! deny = ["postgres", "/opt/shared/bin/*"]
+//! # Never require --force (PID 1 and the deny list still apply)
+//! allow = ["nginx"]
+//! ```
+//!
+//! Patterns are case-insensitive globs matched against the process name,
+//! or against the executable path if they contain a `/`.
+
+use crate::app_detector::Glob;
+use crate::config;
+use crate::error::{PortsError, Result};
+use crate::models::{AppCategory, PortEntry, ProcessInfo};
+use serde::Deserialize;
+
+/// Name of the protection file in the configuration directory.
+pub const PROTECTION_FILE_NAME: &str = "protection.toml";
+
+/// Hint for signals refused because the process belongs to another user.
+pub const OTHER_USER_HINT: &str =
+    "The process belongs to another user; run with sudo to stop it, or ask its owner";
+
+/// Process names of system daemons that are always protected.
+const SYSTEM_PROCESS_NAMES: &[&str] = &[
+    // macOS
+    "launchd",
+    "kernel_task",
+    "WindowServer",
+    "loginwindow",
+    "mDNSResponder",
+    "configd",
+    "rapportd",
+    "sharingd",
+    "ControlCenter",
+    // Linux
+    "init",
+    "systemd",
+    "systemd-*",
+    "dbus-daemon",
+    "NetworkManager",
+    "avahi-daemon",
+    "chronyd",
+    "cupsd",
+    // Both
+    "sshd",
+    "dockerd",
+    "containerd",
+];
+
+/// Directories holding system executables.
+const SYSTEM_EXECUTABLE_DIRS: &[&str] = &[
+    "/sbin/",
+    "/usr/sbin/",
+    "/usr/libexec/",
+    "/System/",
+    "/lib/systemd/",
+    "/usr/lib/systemd/",
+];
+
+/// Contents of the protection file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProtectionFile {
+    #[serde(default)]
+    deny: Vec<String>,
+    #[serde(default)]
+    allow: Vec<String>,
+}
+
+/// Why a process is protected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Protection {
+    /// PID 1 (launchd or systemd)
+    InitProcess,
+    /// Matched the user's deny list
+    Denied { pattern: String },
+    /// A well-known system daemon
+    SystemProcess,
+    /// Runs an executable from a system directory
+    SystemExecutable { dir: &'static str },
+    /// A database or broker whose parent is PID 1, likely a shared service
+    ///
+    /// Only direct children of init are recognized: every process descends
+    /// from init, so walking further up the parent chain would tell nothing.
+    /// Services started under a supervisor (e.g. a process manager that is
+    /// itself a child of init) can be protected with the deny list.
+    InitChildService,
+    /// Belongs to another user (only checked when running as root)
+    OtherUser { user: String },
+}
+
+impl Protection {
+    /// Returns a description of why the process is protected.
+    pub fn reason(&self) -> String {
+        match self {
+            Protection::InitProcess => "it is the init process".to_string(),
+            Protection::Denied { pattern } => {
+                format!("it matches '{}' in {}", pattern, PROTECTION_FILE_NAME)
+            }
+            Protection::SystemProcess => "it is a system daemon".to_string(),
+            Protection::SystemExecutable { dir } => {
+                format!("its executable is in the system directory {}", dir)
+            }
+            Protection::InitChildService => {
+                "it is a service started directly by the init system".to_string()
+            }
+            Protection::OtherUser { user } => format!("it belongs to user {}", user),
+        }
+    }
+}
+
+/// A pattern from the protection file.
+#[derive(Debug, Clone)]
+struct Pattern {
+    text: String,
+    glob: Glob,
+}
+
+impl Pattern {
+    fn new(text: impl AsRef<str>) -> Self {
+        let text = text.as_ref();
+        Self {
+            text: text.to_string(),
+            glob: Glob::new(text),
+        }
+    }
+
+    /// Matches the executable path for patterns containing a `/`, and the
+    /// process name otherwise.
+    fn matches(&self, process: &ProcessInfo) -> bool {
+        if self.text.contains('/') {
+            process
+                .exe
+                .as_ref()
+                .is_some_and(|exe| self.glob.matches(&exe.to_string_lossy().to_lowercase()))
+        } else {
+            self.glob.matches(&process.name.to_lowercase())
+        }
+    }
+}
+
+/// Decides which processes may be stopped.
+#[derive(Debug, Clone)]
+pub struct ProtectionPolicy {
+    deny: Vec<Pattern>,
+    allow: Vec<Pattern>,
+    system_names: Vec<Glob>,
+    /// Effective UID of this process
+    current_uid: u32,
+}
+
+impl ProtectionPolicy {
+    /// Returns the built-in policy for a user.
+    pub fn builtin(current_uid: u32) -> Self {
+        Self {
+            deny: Vec::new(),
+            allow: Vec::new(),
+            system_names: SYSTEM_PROCESS_NAMES.iter().map(|n| Glob::new(n)).collect(),
+            current_uid,
+        }
+    }
+
+    /// Returns the built-in policy for the current user combined with the
+    /// user's protection file.
+    pub fn load() -> Result<Self> {
+        // SAFETY: geteuid has no preconditions and cannot fail
+        let mut policy = Self::builtin(unsafe { libc::geteuid() });
+
+        if let Some((path, content)) = config::read_config_file(PROTECTION_FILE_NAME)? {
+            let file: ProtectionFile = toml::from_str(&content).map_err(|e| {
+                PortsError::config_error(format!("{}: {}", path.display(), e.message()))
+            })?;
+            policy.merge(file);
+        }
+
+        Ok(policy)
+    }
+
+    /// Adds the patterns of a protection file.
+    fn merge(&mut self, file: ProtectionFile) {
+        self.deny.extend(file.deny.iter().map(Pattern::new));
+        self.allow.extend(file.allow.iter().map(Pattern::new));
+    }
+
+    /// Returns why the process of an entry is protected, if it is.
+    pub fn check(&self, entry: &PortEntry) -> Option<Protection> {
+        let process = &entry.process;
+
+        if process.pid == 1 {
+            return Some(Protection::InitProcess);
+        }
+        if let Some(pattern) = self.deny.iter().find(|p| p.matches(process)) {
+            return Some(Protection::Denied {
+                pattern: pattern.text.clone(),
+            });
+        }
+        if self.allow.iter().any(|p| p.matches(process)) {
+            return None;
+        }
+
+        let name = process.name.to_lowercase();
+        if self.system_names.iter().any(|glob| glob.matches(&name)) {
+            return Some(Protection::SystemProcess);
+        }
+
+        let exe = process.exe.as_ref().map(|exe| exe.to_string_lossy());
+        if let Some(dir) = exe.and_then(|exe| {
+            SYSTEM_EXECUTABLE_DIRS
+                .iter()
+                .find(|dir| exe.starts_with(*dir))
+        }) {
+            return Some(Protection::SystemExecutable { dir });
+        }
+
+        let is_service = entry
+            .app_type
+            .as_ref()
+            .is_some_and(|t| matches!(t.category(), AppCategory::Database | AppCategory::Broker));
+        if is_service && process.ppid == Some(1) {
+            return Some(Protection::InitChildService);
+        }
+
+        match process.uid {
+            Some(uid) if self.current_uid == 0 && uid != 0 => Some(Protection::OtherUser {
+                user: process.user.clone().unwrap_or_else(|| uid.to_string()),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Fails if the process belongs to another user and this process lacks
+    /// the privileges to signal it.
+    pub fn check_permission(&self, process: &ProcessInfo) -> Result<()> {
+        match process.uid {
+            Some(uid) if self.current_uid != 0 && uid != self.current_uid => {
+                Err(PortsError::permission_denied_with_hint(
+                    format!(
+                        "PID {} ({}) belongs to user {}",
+                        process.pid,
+                        process.name,
+                        process.user.clone().unwrap_or_else(|| uid.to_string())
+                    ),
+                    OTHER_USER_HINT,
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{AppType, Protocol};
+    use std::path::PathBuf;
+
+    const USER: u32 = 501;
+
+    fn entry(pid: u32, name: &str, exe: &str) -> PortEntry {
+        let mut process = ProcessInfo::new(pid, name);
+        process.exe = Some(PathBuf::from(exe));
+        process.uid = Some(USER);
+        process.ppid = Some(4000);
+        PortEntry::new(8000, Protocol::Tcp, process)
+    }
+
+    fn policy_with(content: &str) -> ProtectionPolicy {
+        let mut policy = ProtectionPolicy::builtin(USER);
+        policy.merge(toml::from_str(content).unwrap());
+        policy
+    }
+
+    #[test]
+    fn test_builtin_protections() {
+        let policy = ProtectionPolicy::builtin(USER);
+
+        let cases = [
+            (
+                entry(1, "systemd", "/usr/lib/systemd/systemd"),
+                Some(Protection::InitProcess),
+            ),
+            (
+                entry(800, "sshd", "/usr/bin/sshd"),
+                Some(Protection::SystemProcess),
+            ),
+            (
+                entry(801, "systemd-resolved", "/usr/bin/systemd-resolved"),
+                Some(Protection::SystemProcess),
+            ),
+            (
+                entry(802, "rpcbind", "/usr/sbin/rpcbind"),
+                Some(Protection::SystemExecutable { dir: "/usr/sbin/" }),
+            ),
+            (entry(4321, "node", "/usr/bin/node"), None),
+        ];
+
+        for (entry, expected) in cases {
+            assert_eq!(policy.check(&entry), expected, "{}", entry.process.name);
+        }
+    }
+
+    #[test]
+    fn test_init_child_service() {
+        let policy = ProtectionPolicy::builtin(USER);
+        let mut postgres =
+            entry(364, "postgres", "/opt/homebrew/bin/postgres").with_app_type(AppType::PostgreSql);
+        assert_eq!(policy.check(&postgres), None);
+
+        postgres.process.ppid = Some(1);
+        assert_eq!(policy.check(&postgres), Some(Protection::InitChildService));
+
+        // Only direct children of init, not those started by a supervisor
+        postgres.process.ppid = Some(812);
+        assert_eq!(policy.check(&postgres), None);
+
+        // A dev server reparented to init is not a managed service
+        let mut node = entry(4321, "node", "/usr/bin/node").with_app_type(AppType::NodeJs);
+        node.process.ppid = Some(1);
+        assert_eq!(policy.check(&node), None);
+    }
+
+    #[test]
+    fn test_deny_and_allow_lists() {
+        let policy = policy_with(
+            r#"
+            deny = ["postgres", "/opt/shared/*"]
+            allow = ["SSHD", "postgres", "rpcbind"]
+            "#,
+        );
+
+        assert_eq!(policy.check(&entry(800, "sshd", "/usr/bin/sshd")), None);
+        assert_eq!(
+            policy.check(&entry(802, "rpcbind", "/usr/sbin/rpcbind")),
+            None
+        );
+        // The deny list wins over the allow list
+        assert_eq!(
+            policy.check(&entry(364, "postgres", "/usr/bin/postgres")),
+            Some(Protection::Denied {
+                pattern: "postgres".to_string()
+            })
+        );
+        assert!(matches!(
+            policy.check(&entry(900, "api", "/opt/shared/bin/api")),
+            Some(Protection::Denied { .. })
+        ));
+        // PID 1 cannot be allowed
+        let allowing_init = policy_with(r#"allow = ["launchd"]"#);
+        assert_eq!(
+            allowing_init.check(&entry(1, "launchd", "/sbin/launchd")),
+            Some(Protection::InitProcess)
+        );
+    }
+
+    #[test]
+    fn test_other_users() {
+        let mut other = entry(700, "node", "/usr/bin/node");
+        other.process.uid = Some(502);
+        other.process.user = Some("bob".to_string());
+
+        // Running as a regular user, the signal would be refused
+        let policy = ProtectionPolicy::builtin(USER);
+        assert_eq!(policy.check(&other), None);
+        let error = policy.check_permission(&other.process).unwrap_err();
+        assert!(error.to_string().contains("belongs to user bob"));
+        assert_eq!(error.hint(), OTHER_USER_HINT);
+        assert!(policy
+            .check_permission(&entry(701, "node", "/usr/bin/node").process)
+            .is_ok());
+
+        // Running as root, it is allowed but protected
+        let policy = ProtectionPolicy::builtin(0);
+        assert!(policy.check_permission(&other.process).is_ok());
+        assert_eq!(
+            policy.check(&other),
+            Some(Protection::OtherUser {
+                user: "bob".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_invalid_protection_file() {
+        assert!(toml::from_str::<ProtectionFile>("deny = \"postgres\"").is_err());
+        assert!(toml::from_str::<ProtectionFile>("block = []").is_err());
+    }
+}
@@ -0,0 +1,819 @@
+//! Query language for selecting entries with `--where`.
+//!
+//! A query combines comparisons on entry fields with `and`, `or`, `not` and
+//! parentheses:
+//!
+//! ```text
+//! (type = python or type = node) and uptime > 1d and not port in 3000..3999
+//! ```
+//!
+//! | Field      | Kind     | Operators                          |
+//! |------------|----------|------------------------------------|
+//! | `port`     | number   | `=` `!=` `<` `<=` `>` `>=` `in`    |
+//! | `pid`      | number   | `=` `!=` `<` `<=` `>` `>=` `in`    |
+//! | `uptime`   | duration | `=` `!=` `<` `<=` `>` `>=` `in`    |
+//! | `type`     | text     | `=` `!=` `~` `!~`                  |
+//! | `user`     | text     | `=` `!=` `~` `!~`                  |
+//! | `command`  | text     | `=` `!=` `~` `!~`                  |
+//! | `address`  | text     | `=` `!=` `~` `!~`                  |
+//! | `protocol` | text     | `=` `!=`                           |
+//!
+//! `in` takes an inclusive range (`3000..3999`, `1h..1d`), `~` matches a
+//! regular expression and durations are written as `30s`, `15m`, `2h` or
+//! `1d`. `type = node` matches like `--type`, by prefix or category, and
+//! `user` matches the user name or UID. Values containing spaces or
+//! parentheses must be quoted. A comparison with a value that is not known,
+//! such as the uptime of a process whose start time could not be read, is
+//! false.
+
+use crate::error::{PortsError, Result};
+use crate::filter;
+use crate::models::{AppType, PortEntry};
+use regex::Regex;
+use std::fmt;
+use std::net::IpAddr;
+use std::str::FromStr;
+
+/// Fields that can be used in a query.
+const FIELDS: &[(&str, Field)] = &[
+    ("port", Field::Port),
+    ("pid", Field::Pid),
+    ("type", Field::Type),
+    ("user", Field::User),
+    ("uptime", Field::Uptime),
+    ("command", Field::Command),
+    ("address", Field::Address),
+    ("protocol", Field::Protocol),
+];
+
+/// A compiled `--where` query.
+#[derive(Debug, Clone)]
+pub struct Query {
+    expr: Expr,
+}
+
+impl Query {
+    /// Returns true if the entry matches the query.
+    pub fn matches(&self, entry: &PortEntry) -> bool {
+        self.expr.matches(entry)
+    }
+}
+
+impl FromStr for Query {
+    type Err = PortsError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let tokens = tokenize(s)?;
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+            end_column: s.chars().count() + 1,
+        };
+
+        if parser.peek().is_none() {
+            return Err(PortsError::invalid_query("the query is empty"));
+        }
+        let expr = parser.or()?;
+        if let Some(token) = parser.peek() {
+            return Err(parser.error_at(token.column, format!("unexpected {}", token)));
+        }
+        Ok(Self { expr })
+    }
+}
+
+/// A field of an entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Port,
+    Pid,
+    Type,
+    User,
+    Uptime,
+    Command,
+    Address,
+    Protocol,
+}
+
+impl Field {
+    fn name(self) -> &'static str {
+        FIELDS
+            .iter()
+            .find(|(_, field)| *field == self)
+            .map_or("?", |(name, _)| name)
+    }
+
+    /// Returns true for fields compared as numbers (durations in seconds).
+    fn is_numeric(self) -> bool {
+        matches!(self, Field::Port | Field::Pid | Field::Uptime)
+    }
+
+    /// Returns the numeric value of the field.
+    fn number(self, entry: &PortEntry) -> Option<u64> {
+        match self {
+            Field::Port => Some(u64::from(entry.port)),
+            Field::Pid => Some(u64::from(entry.process.pid)),
+            Field::Uptime => entry.process.elapsed.map(|elapsed| elapsed.as_secs()),
+            _ => None,
+        }
+    }
+
+    /// Returns the texts a regular expression is matched against.
+    fn texts(self, entry: &PortEntry) -> Vec<String> {
+        let process = &entry.process;
+        match self {
+            Field::Type => {
+                let app_type = entry.app_type.clone().unwrap_or(AppType::Unknown);
+                vec![
+                    app_type.display_name().to_string(),
+                    app_type.id().to_string(),
+                ]
+            }
+            Field::User => process
+                .user
+                .iter()
+                .cloned()
+                .chain(process.uid.map(|uid| uid.to_string()))
+                .collect(),
+            Field::Command => vec![process
+                .command
+                .clone()
+                .unwrap_or_else(|| process.name.clone())],
+            Field::Address => entry
+                .bind_address
+                .map(|address| address.to_string())
+                .into_iter()
+                .collect(),
+            Field::Protocol => vec![entry.protocol.to_string()],
+            Field::Port | Field::Pid | Field::Uptime => Vec::new(),
+        }
+    }
+
+    /// Returns whether the field equals a value, or `None` if the field is
+    /// not known.
+    fn equals(self, entry: &PortEntry, value: &str) -> Option<bool> {
+        let texts = self.texts(entry);
+        if texts.is_empty() {
+            return None;
+        }
+        Some(match self {
+            Field::Type => filter::type_matches(entry, value),
+            Field::Protocol => texts[0].eq_ignore_ascii_case(value),
+            _ => texts.iter().any(|text| text == value),
+        })
+    }
+}
+
+/// A comparison operator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Match,
+    NotMatch,
+}
+
+impl Op {
+    /// Operators, longest first so that `<=` is not read as `<`.
+    const ALL: [(&'static str, Op); 9] = [
+        ("==", Op::Eq),
+        ("!=", Op::Ne),
+        ("<=", Op::Le),
+        (">=", Op::Ge),
+        ("!~", Op::NotMatch),
+        ("=", Op::Eq),
+        ("<", Op::Lt),
+        (">", Op::Gt),
+        ("~", Op::Match),
+    ];
+
+    fn compare(self, left: u64, right: u64) -> bool {
+        match self {
+            Op::Eq => left == right,
+            Op::Ne => left != right,
+            Op::Lt => left < right,
+            Op::Le => left <= right,
+            Op::Gt => left > right,
+            Op::Ge => left >= right,
+            Op::Match | Op::NotMatch => false,
+        }
+    }
+}
+
+impl fmt::Display for Op {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = Op::ALL
+            .iter()
+            .rev()
+            .find(|(_, op)| op == self)
+            .map_or("?", |(symbol, _)| symbol);
+        write!(f, "{}", symbol)
+    }
+}
+
+/// A node of a parsed query.
+#[derive(Debug, Clone)]
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare(Field, Op, u64),
+    Within(Field, u64, u64),
+    Equals {
+        field: Field,
+        value: String,
+        negate: bool,
+    },
+    Matches {
+        field: Field,
+        regex: Regex,
+        negate: bool,
+    },
+}
+
+impl Expr {
+    fn matches(&self, entry: &PortEntry) -> bool {
+        match self {
+            Expr::And(left, right) => left.matches(entry) && right.matches(entry),
+            Expr::Or(left, right) => left.matches(entry) || right.matches(entry),
+            Expr::Not(expr) => !expr.matches(entry),
+            Expr::Compare(field, op, value) => field
+                .number(entry)
+                .is_some_and(|number| op.compare(number, *value)),
+            Expr::Within(field, start, end) => field
+                .number(entry)
+                .is_some_and(|number| (*start..=*end).contains(&number)),
+            Expr::Equals {
+                field,
+                value,
+                negate,
+            } => field
+                .equals(entry, value)
+                .is_some_and(|equal| equal != *negate),
+            Expr::Matches {
+                field,
+                regex,
+                negate,
+            } => {
+                let texts = field.texts(entry);
+                !texts.is_empty() && texts.iter().any(|text| regex.is_match(text)) != *negate
+            }
+        }
+    }
+}
+
+/// A token of a query, with the column where it starts.
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    column: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    LeftParen,
+    RightParen,
+    Op(Op),
+    /// An unquoted word: a field name, keyword or value
+    Word(String),
+    /// A quoted value
+    Quoted(String),
+}
+
+impl Token {
+    /// Returns true if the token is the keyword `keyword`.
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(&self.kind, TokenKind::Word(word) if word.eq_ignore_ascii_case(keyword))
+    }
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            TokenKind::LeftParen => write!(f, "'('"),
+            TokenKind::RightParen => write!(f, "')'"),
+            TokenKind::Op(op) => write!(f, "'{}'", op),
+            TokenKind::Word(word) => write!(f, "'{}'", word),
+            TokenKind::Quoted(value) => write!(f, "\"{}\"", value),
+        }
+    }
+}
+
+/// Splits a query into tokens.
+fn tokenize(s: &str) -> Result<Vec<Token>> {
+    let chars: Vec<char> = s.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let column = i + 1;
+
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        let kind = match c {
+            '(' => {
+                i += 1;
+                TokenKind::LeftParen
+            }
+            ')' => {
+                i += 1;
+                TokenKind::RightParen
+            }
+            '"' | '\'' => {
+                let end = chars[i + 1..]
+                    .iter()
+                    .position(|&d| d == c)
+                    .map(|offset| i + 1 + offset)
+                    .ok_or_else(|| {
+                        PortsError::invalid_query(format!(
+                            "unterminated string at column {}",
+                            column
+                        ))
+                    })?;
+                let value = chars[i + 1..end].iter().collect();
+                i = end + 1;
+                TokenKind::Quoted(value)
+            }
+            _ => {
+                let rest: String = chars[i..].iter().take(2).collect();
+                if let Some((symbol, op)) = Op::ALL.iter().find(|(sym, _)| rest.starts_with(sym)) {
+                    i += symbol.chars().count();
+                    TokenKind::Op(*op)
+                } else {
+                    let start = i;
+                    while i < chars.len() && !ends_word(chars[i]) {
+                        i += 1;
+                    }
+                    if start == i {
+                        return Err(PortsError::invalid_query(format!(
+                            "unexpected '{}' at column {}",
+                            c, column
+                        )));
+                    }
+                    TokenKind::Word(chars[start..i].iter().collect())
+                }
+            }
+        };
+        tokens.push(Token { kind, column });
+    }
+
+    Ok(tokens)
+}
+
+/// Returns true for characters that end an unquoted word.
+fn ends_word(c: char) -> bool {
+    c.is_whitespace() || matches!(c, '(' | ')' | '"' | '\'' | '=' | '!' | '<' | '>' | '~')
+}
+
+/// Recursive descent parser. `not` binds tighter than `and`, which binds
+/// tighter than `or`.
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    /// Column just past the end of the query, for errors at the end
+    end_column: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    /// Consumes the next token if it is the keyword `keyword`.
+    fn keyword(&mut self, keyword: &str) -> bool {
+        let found = self.peek().is_some_and(|t| t.is_keyword(keyword));
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn error_at(&self, column: usize, message: impl fmt::Display) -> PortsError {
+        PortsError::invalid_query(format!("{} at column {}", message, column))
+    }
+
+    /// Returns the next token, or an error naming what was expected.
+    fn expect(&mut self, expected: &str) -> Result<Token> {
+        match self.next() {
+            Some(token) => Ok(token),
+            None => Err(self.error_at(
+                self.end_column,
+                format!("expected {}, found the end of the query", expected),
+            )),
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr> {
+        let mut expr = self.and()?;
+        while self.keyword("or") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr> {
+        let mut expr = self.not()?;
+        while self.keyword("and") {
+            expr = Expr::And(Box::new(expr), Box::new(self.not()?));
+        }
+        Ok(expr)
+    }
+
+    fn not(&mut self) -> Result<Expr> {
+        if self.keyword("not") {
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr> {
+        let token = self.expect("a field name or '('")?;
+        match &token.kind {
+            TokenKind::LeftParen => {
+                let expr = self.or()?;
+                let close = self.expect("')'")?;
+                if close.kind != TokenKind::RightParen {
+                    return Err(
+                        self.error_at(close.column, format!("expected ')', found {}", close))
+                    );
+                }
+                Ok(expr)
+            }
+            TokenKind::Word(name) if !is_reserved(name) => {
+                let field = FIELDS
+                    .iter()
+                    .find(|(field, _)| field.eq_ignore_ascii_case(name))
+                    .map(|(_, field)| *field)
+                    .ok_or_else(|| {
+                        let names: Vec<&str> = FIELDS.iter().map(|(n, _)| *n).collect();
+                        self.error_at(
+                            token.column,
+                            format!(
+                                "unknown field '{}' (expected one of {})",
+                                name,
+                                names.join(", ")
+                            ),
+                        )
+                    })?;
+                self.comparison(field)
+            }
+            _ => Err(self.error_at(
+                token.column,
+                format!("expected a field name or '(', found {}", token),
+            )),
+        }
+    }
+
+    /// Parses the operator and value following a field name.
+    fn comparison(&mut self, field: Field) -> Result<Expr> {
+        let operator = self.expect(&format!("an operator after '{}'", field.name()))?;
+        let op = match operator.kind {
+            TokenKind::Op(op) => op,
+            _ if operator.is_keyword("in") => return self.range(field, operator.column),
+            _ => {
+                return Err(self.error_at(
+                    operator.column,
+                    format!(
+                        "expected an operator after '{}', found {}",
+                        field.name(),
+                        operator
+                    ),
+                ))
+            }
+        };
+
+        let (value, column) = self.value(&format!("a value after '{}'", op))?;
+        let unsupported = |parser: &Self| {
+            parser.error_at(
+                operator.column,
+                format!("'{}' cannot be used with '{}'", op, field.name()),
+            )
+        };
+
+        match op {
+            _ if field.is_numeric() => {
+                if matches!(op, Op::Match | Op::NotMatch) {
+                    return Err(unsupported(self));
+                }
+                Ok(Expr::Compare(
+                    field,
+                    op,
+                    self.number(field, &value, column)?,
+                ))
+            }
+            Op::Eq | Op::Ne => {
+                let is_protocol = ["tcp", "udp"]
+                    .iter()
+                    .any(|p| p.eq_ignore_ascii_case(&value));
+                if field == Field::Protocol && !is_protocol {
+                    return Err(self.error_at(
+                        column,
+                        format!("invalid protocol '{}' (expected tcp or udp)", value),
+                    ));
+                }
+                // Compare addresses in their canonical form
+                let value = match (field, value.parse::<IpAddr>()) {
+                    (Field::Address, Ok(address)) => address.to_string(),
+                    _ => value,
+                };
+                Ok(Expr::Equals {
+                    field,
+                    value,
+                    negate: op == Op::Ne,
+                })
+            }
+            Op::Match | Op::NotMatch if field != Field::Protocol => {
+                let regex = Regex::new(&value).map_err(|e| {
+                    self.error_at(column, format!("invalid regular expression: {}", e))
+                })?;
+                Ok(Expr::Matches {
+                    field,
+                    regex,
+                    negate: op == Op::NotMatch,
+                })
+            }
+            _ => Err(unsupported(self)),
+        }
+    }
+
+    /// Parses the range following `field in`.
+    fn range(&mut self, field: Field, column: usize) -> Result<Expr> {
+        if !field.is_numeric() {
+            return Err(self.error_at(
+                column,
+                format!("'in' cannot be used with '{}'", field.name()),
+            ));
+        }
+
+        let (value, column) = self.value("a range after 'in'")?;
+        let (start, end) = value.split_once("..").ok_or_else(|| {
+            self.error_at(
+                column,
+                format!("invalid range '{}' (expected e.g. 3000..3999)", value),
+            )
+        })?;
+        let start = self.number(field, start, column)?;
+        let end = self.number(field, end, column)?;
+        if start > end {
+            return Err(self.error_at(
+                column,
+                format!(
+                    "invalid range '{}' (the start is greater than the end)",
+                    value
+                ),
+            ));
+        }
+        Ok(Expr::Within(field, start, end))
+    }
+
+    /// Returns the next value and its column.
+    fn value(&mut self, expected: &str) -> Result<(String, usize)> {
+        let token = self.expect(expected)?;
+        match token.kind {
+            TokenKind::Quoted(value) => Ok((value, token.column)),
+            TokenKind::Word(word) if !is_reserved(&word) => Ok((word, token.column)),
+            _ => Err(self.error_at(
+                token.column,
+                format!("expected {}, found {}", expected, token),
+            )),
+        }
+    }
+
+    /// Parses a number, or a duration in seconds for `uptime`.
+    fn number(&self, field: Field, value: &str, column: usize) -> Result<u64> {
+        match field {
+            Field::Uptime => parse_age(value).ok_or_else(|| {
+                self.error_at(
+                    column,
+                    format!(
+                        "invalid duration '{}' (expected e.g. 30s, 15m, 2h or 1d)",
+                        value
+                    ),
+                )
+            }),
+            Field::Port => value
+                .parse::<u16>()
+                .map(u64::from)
+                .map_err(|_| self.error_at(column, format!("invalid port '{}'", value))),
+            _ => value
+                .parse::<u32>()
+                .map(u64::from)
+                .map_err(|_| self.error_at(column, format!("invalid number '{}'", value))),
+        }
+    }
+}
+
+/// Returns true for words that cannot be field names or unquoted values.
+fn is_reserved(word: &str) -> bool {
+    ["and", "or", "not", "in"]
+        .iter()
+        .any(|keyword| keyword.eq_ignore_ascii_case(word))
+}
+
+/// Parses a duration such as `90`, `30s`, `15m`, `2h` or `1d` into seconds.
+fn parse_age(s: &str) -> Option<u64> {
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let number: u64 = number.parse().ok()?;
+    let seconds = match unit {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => return None,
+    };
+    number.checked_mul(seconds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ProcessInfo, Protocol};
+    use std::time::Duration;
+
+    fn entries() -> Vec<PortEntry> {
+        let mut node = ProcessInfo::new(4242, "node")
+            .with_command("node server.js --port 3000")
+            .with_user("alice");
+        node.elapsed = Some(Duration::from_secs(2 * 86400));
+        let mut python = ProcessInfo::new(5151, "python3")
+            .with_command("python manage.py runserver 0.0.0.0:8000")
+            .with_user("alice");
+        python.elapsed = Some(Duration::from_secs(600));
+        let mut postgres = ProcessInfo::new(99, "postgres").with_user("postgres");
+        postgres.uid = Some(70);
+        postgres.elapsed = Some(Duration::from_secs(30 * 86400));
+
+        vec![
+            PortEntry::new(3000, Protocol::Tcp, node)
+                .with_bind_address("::1".parse().unwrap())
+                .with_app_type(AppType::NodeJs),
+            PortEntry::new(5432, Protocol::Tcp, postgres)
+                .with_bind_address("127.0.0.1".parse().unwrap())
+                .with_app_type(AppType::PostgreSql),
+            PortEntry::new(8000, Protocol::Tcp, python)
+                .with_bind_address("0.0.0.0".parse().unwrap())
+                .with_app_type(AppType::Python),
+            PortEntry::new(5353, Protocol::Udp, ProcessInfo::new(300, "mdns")),
+        ]
+    }
+
+    fn ports(query: &str) -> Vec<u16> {
+        let query: Query = query.parse().unwrap();
+        entries()
+            .iter()
+            .filter(|entry| query.matches(entry))
+            .map(|entry| entry.port)
+            .collect()
+    }
+
+    fn error(query: &str) -> String {
+        query.parse::<Query>().unwrap_err().to_string()
+    }
+
+    #[test]
+    fn test_comparisons() {
+        let cases: &[(&str, &[u16])] = &[
+            ("port = 3000", &[3000]),
+            ("port == 3000", &[3000]),
+            ("port != 3000", &[5432, 8000, 5353]),
+            ("port >= 5432", &[5432, 8000]),
+            ("pid < 300", &[5432]),
+            ("port in 5000..5999", &[5432, 5353]),
+            ("uptime > 1d", &[3000, 5432]),
+            ("uptime in 5m..1h", &[8000]),
+            ("type = node", &[3000]),
+            ("type = database", &[5432]),
+            ("type ~ '^Postgre'", &[5432]),
+            ("user = alice", &[3000, 8000]),
+            ("user = 70", &[5432]),
+            ("user != alice", &[5432]),
+            ("command ~ 'manage\\.py'", &[8000]),
+            ("command !~ server", &[5432, 5353]),
+            ("command = postgres", &[5432]),
+            ("address = 0:0::1", &[3000]),
+            ("address ~ \"^127\\.\"", &[5432]),
+            ("protocol = udp", &[5353]),
+            ("PORT = 3000", &[3000]),
+        ];
+
+        for (query, expected) in cases {
+            assert_eq!(ports(query), *expected, "{}", query);
+        }
+    }
+
+    #[test]
+    fn test_boolean_operators() {
+        let cases: &[(&str, &[u16])] = &[
+            ("type = python or type = node", &[3000, 8000]),
+            ("user = alice and port > 5000", &[8000]),
+            ("not port in 3000..3999", &[5432, 8000, 5353]),
+            // and binds tighter than or
+            ("port = 5353 or user = alice and uptime > 1d", &[3000, 5353]),
+            (
+                "(type = python or type = node) and uptime > 1h and not port in 3000..3999",
+                &[],
+            ),
+            (
+                "(type = python OR type = node) AND NOT (port in 3000..3999)",
+                &[8000],
+            ),
+            ("not not protocol = tcp", &[3000, 5432, 8000]),
+        ];
+
+        for (query, expected) in cases {
+            assert_eq!(ports(query), *expected, "{}", query);
+        }
+    }
+
+    #[test]
+    fn test_unknown_values_never_match() {
+        // The mdns entry has no user, uptime or address
+        assert_eq!(ports("user != alice"), vec![5432]);
+        assert_eq!(ports("uptime < 1d"), vec![8000]);
+        assert_eq!(ports("not uptime < 1d"), vec![3000, 5432, 5353]);
+        assert_eq!(ports("address !~ ."), Vec::<u16>::new());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let cases = [
+            ("", "the query is empty"),
+            ("   ", "the query is empty"),
+            (
+                "name = node",
+                "unknown field 'name' (expected one of port, pid",
+            ),
+            (
+                "port",
+                "expected an operator after 'port', found the end of the query at column 5",
+            ),
+            (
+                "port 3000",
+                "expected an operator after 'port', found '3000' at column 6",
+            ),
+            (
+                "port = ",
+                "expected a value after '=', found the end of the query at column 8",
+            ),
+            ("port = abc", "invalid port 'abc' at column 8"),
+            ("port = 70000", "invalid port '70000'"),
+            ("uptime > 1y", "invalid duration '1y'"),
+            ("port ~ 80", "'~' cannot be used with 'port' at column 6"),
+            ("user < bob", "'<' cannot be used with 'user'"),
+            ("protocol ~ t", "'~' cannot be used with 'protocol'"),
+            ("protocol = sctp", "invalid protocol 'sctp'"),
+            ("user in a..b", "'in' cannot be used with 'user'"),
+            ("port in 3000", "invalid range '3000'"),
+            ("port in 4000..3000", "the start is greater than the end"),
+            ("command ~ '('", "invalid regular expression"),
+            ("command = 'node", "unterminated string at column 11"),
+            (
+                "(port = 1",
+                "expected ')', found the end of the query at column 10",
+            ),
+            ("port = 1)", "unexpected ')' at column 9"),
+            ("port = 1 port = 2", "unexpected 'port' at column 10"),
+            (
+                "port = 1 and",
+                "expected a field name or '(', found the end of the query",
+            ),
+            (
+                "and port = 1",
+                "expected a field name or '(', found 'and' at column 1",
+            ),
+            ("port = and", "expected a value after '=', found 'and'"),
+            ("!port = 1", "unexpected '!' at column 1"),
+        ];
+
+        for (query, expected) in cases {
+            let message = error(query);
+            assert!(message.starts_with("Invalid query: "), "{}", message);
+            assert!(message.contains(expected), "{}: {}", query, message);
+        }
+    }
+
+    #[test]
+    fn test_parse_age() {
+        assert_eq!(parse_age("90"), Some(90));
+        assert_eq!(parse_age("30s"), Some(30));
+        assert_eq!(parse_age("15m"), Some(900));
+        assert_eq!(parse_age("2h"), Some(7200));
+        assert_eq!(parse_age("1d"), Some(86400));
+        assert_eq!(parse_age("d"), None);
+        assert_eq!(parse_age("1.5h"), None);
+    }
+}
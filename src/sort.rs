@@ -0,0 +1,247 @@
+//! Ordering and grouping of listed entries.
+//!
+//! Entries are compared on typed values (ports and PIDs as numbers, uptime
+//! as a duration), so `--sort uptime` orders `2h` before `1d`. Entries
+//! whose value is unknown sort after the others (before them with
+//! `--reverse`), and entries that compare equal keep their port order.
+
+use crate::models::{AppType, PortEntry};
+use std::cmp::Ordering;
+
+/// A value entries can be sorted by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum SortKey {
+    /// Port number, TCP before UDP
+    Port,
+    /// Process ID
+    Pid,
+    /// Application type
+    Type,
+    /// Project the process runs in
+    Project,
+    /// Time since the process started
+    Uptime,
+    /// User running the process
+    User,
+    /// Command line
+    Command,
+}
+
+impl SortKey {
+    /// Compares two entries by this key.
+    pub fn compare(self, a: &PortEntry, b: &PortEntry) -> Ordering {
+        match self {
+            SortKey::Port => (a.port, a.protocol).cmp(&(b.port, b.protocol)),
+            SortKey::Pid => a.process.pid.cmp(&b.process.pid),
+            SortKey::Type => compare_known(type_name(a), type_name(b)),
+            SortKey::Project => compare_known(
+                a.process.project_name.as_deref().map(str::to_lowercase),
+                b.process.project_name.as_deref().map(str::to_lowercase),
+            ),
+            SortKey::Uptime => compare_known(a.process.elapsed, b.process.elapsed),
+            SortKey::User => compare_known(
+                a.process.user.as_deref().map(str::to_lowercase),
+                b.process.user.as_deref().map(str::to_lowercase),
+            ),
+            SortKey::Command => command(a).cmp(command(b)),
+        }
+    }
+}
+
+/// A value entries can be grouped by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum GroupBy {
+    /// Application type
+    Type,
+    /// User running the process
+    User,
+}
+
+impl GroupBy {
+    /// Returns the heading of the group an entry belongs to.
+    pub fn label(self, entry: &PortEntry) -> String {
+        match self {
+            GroupBy::Type => entry
+                .app_type
+                .as_ref()
+                .unwrap_or(&AppType::Unknown)
+                .display_name()
+                .to_string(),
+            GroupBy::User => entry
+                .process
+                .user
+                .clone()
+                .or_else(|| entry.process.uid.map(|uid| format!("uid {}", uid)))
+                .unwrap_or_else(|| "不明なユーザー".to_string()),
+        }
+    }
+
+    /// Compares the groups of two entries; unknown groups come last.
+    fn compare(self, a: &PortEntry, b: &PortEntry) -> Ordering {
+        match self {
+            GroupBy::Type => compare_known(type_name(a), type_name(b)),
+            GroupBy::User => compare_known(self.known_user(a), self.known_user(b)),
+        }
+    }
+
+    fn known_user(self, entry: &PortEntry) -> Option<String> {
+        (entry.process.user.is_some() || entry.process.uid.is_some())
+            .then(|| self.label(entry).to_lowercase())
+    }
+}
+
+/// Sorts entries by `keys`, each breaking ties of the previous one.
+///
+/// `reverse` reverses the order of the keys. With `group_by`, entries are
+/// first ordered by group, so that each group is contiguous.
+pub fn sort_entries(
+    entries: &mut [PortEntry],
+    keys: &[SortKey],
+    reverse: bool,
+    group_by: Option<GroupBy>,
+) {
+    entries.sort_by(|a, b| compare_entries(a, b, keys, reverse, group_by));
+}
+
+/// Compares two entries the way [`sort_entries`] orders them.
+pub fn compare_entries(
+    a: &PortEntry,
+    b: &PortEntry,
+    keys: &[SortKey],
+    reverse: bool,
+    group_by: Option<GroupBy>,
+) -> Ordering {
+    let group = group_by.map_or(Ordering::Equal, |group| group.compare(a, b));
+    let order = keys.iter().fold(Ordering::Equal, |order, key| {
+        order.then_with(|| key.compare(a, b))
+    });
+    group.then(if reverse { order.reverse() } else { order })
+}
+
+/// Compares optional values, ordering unknown values after known ones.
+fn compare_known<T: Ord>(a: Option<T>, b: Option<T>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+/// Returns the lowercase display name of the application type, or `None`
+/// if it is unknown.
+fn type_name(entry: &PortEntry) -> Option<String> {
+    entry
+        .app_type
+        .as_ref()
+        .filter(|app_type| **app_type != AppType::Unknown)
+        .map(|app_type| app_type.display_name().to_lowercase())
+}
+
+/// Returns the command line of an entry, or its process name.
+fn command(entry: &PortEntry) -> &str {
+    entry
+        .process
+        .command
+        .as_deref()
+        .unwrap_or(&entry.process.name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ProcessInfo, Protocol};
+    use std::time::Duration;
+
+    fn entry(port: u16, app_type: AppType, user: Option<&str>, uptime: Option<u64>) -> PortEntry {
+        let mut process = ProcessInfo::new(u32::from(port) + 1000, "test");
+        process.user = user.map(str::to_string);
+        process.elapsed = uptime.map(Duration::from_secs);
+        process.command = Some(format!("server --port {}", port));
+        if app_type == AppType::NodeJs {
+            process.project_name = Some(format!("web-{}", 9000 - port));
+        }
+        PortEntry::new(port, Protocol::Tcp, process).with_app_type(app_type)
+    }
+
+    fn entries() -> Vec<PortEntry> {
+        vec![
+            entry(3000, AppType::NodeJs, Some("alice"), Some(7200)),
+            entry(3001, AppType::NodeJs, Some("bob"), Some(86400)),
+            entry(5432, AppType::PostgreSql, Some("postgres"), None),
+            entry(8000, AppType::Python, Some("alice"), Some(60)),
+            entry(9999, AppType::Unknown, None, Some(600)),
+        ]
+    }
+
+    fn sorted(keys: &[SortKey], reverse: bool, group_by: Option<GroupBy>) -> Vec<u16> {
+        let mut entries = entries();
+        sort_entries(&mut entries, keys, reverse, group_by);
+        entries.iter().map(|e| e.port).collect()
+    }
+
+    #[test]
+    fn test_sort_by_single_key() {
+        assert_eq!(
+            sorted(&[SortKey::Port], true, None),
+            vec![9999, 8000, 5432, 3001, 3000]
+        );
+        assert_eq!(
+            sorted(&[SortKey::Pid], false, None),
+            vec![3000, 3001, 5432, 8000, 9999]
+        );
+        // By duration rather than the formatted "2h" and "1d"; unknown last
+        assert_eq!(
+            sorted(&[SortKey::Uptime], false, None),
+            vec![8000, 9999, 3000, 3001, 5432]
+        );
+        assert_eq!(
+            sorted(&[SortKey::Uptime], true, None),
+            vec![5432, 3001, 3000, 9999, 8000]
+        );
+        assert_eq!(
+            sorted(&[SortKey::User], false, None),
+            vec![3000, 8000, 3001, 5432, 9999]
+        );
+        // Only the Node.js entries have a project
+        assert_eq!(
+            sorted(&[SortKey::Project], false, None),
+            vec![3001, 3000, 5432, 8000, 9999]
+        );
+    }
+
+    #[test]
+    fn test_sort_by_multiple_keys() {
+        assert_eq!(
+            sorted(&[SortKey::Type, SortKey::Port], false, None),
+            vec![3000, 3001, 5432, 8000, 9999]
+        );
+        assert_eq!(
+            sorted(&[SortKey::Type, SortKey::Uptime], true, None),
+            vec![9999, 8000, 5432, 3001, 3000]
+        );
+        assert_eq!(
+            sorted(&[SortKey::User, SortKey::Uptime], false, None),
+            vec![8000, 3000, 3001, 5432, 9999]
+        );
+    }
+
+    #[test]
+    fn test_group_entries() {
+        // Groups stay in ascending order when the keys are reversed
+        assert_eq!(
+            sorted(&[SortKey::Port], true, Some(GroupBy::User)),
+            vec![8000, 3000, 3001, 5432, 9999]
+        );
+        assert_eq!(
+            sorted(&[SortKey::Uptime], false, Some(GroupBy::Type)),
+            vec![3000, 3001, 5432, 8000, 9999]
+        );
+
+        let entries = entries();
+        assert_eq!(GroupBy::Type.label(&entries[0]), "Node.js");
+        assert_eq!(GroupBy::Type.label(&entries[4]), "Unknown");
+        assert_eq!(GroupBy::User.label(&entries[2]), "postgres");
+        assert_eq!(GroupBy::User.label(&entries[4]), "不明なユーザー");
+    }
+}
@@ -0,0 +1,113 @@
+//! Interactive full-screen port browser (`ports tui`).
+//!
+//! The list refreshes itself periodically by re-running the same scan and
+//! detection pipeline as the table output. [`app`] holds the state and key
+//! bindings and [`ui`] renders it, so both can be tested against ratatui's
+//! `TestBackend` without a terminal.
+
+mod app;
+mod ui;
+
+use crate::error::{PortsError, Result};
+use crate::kill::{self, Signal};
+use crate::models::PortEntry;
+use crate::process_info;
+use crate::protection::ProtectionPolicy;
+use app::{Action, App};
+use crossterm::event::{self, Event, KeyEventKind};
+use ratatui::DefaultTerminal;
+use std::time::{Duration, Instant};
+
+/// Runs the browser until the user quits.
+///
+/// `refresh` scans the listening ports; it is called at start-up, every
+/// `interval` and after stopping a process.
+pub fn run(interval: Duration, refresh: impl FnMut() -> Result<Vec<PortEntry>>) -> Result<()> {
+    let policy = ProtectionPolicy::load()?;
+
+    let mut terminal = ratatui::try_init()?;
+    let result = event_loop(&mut terminal, interval, refresh, &policy);
+    ratatui::restore();
+    result
+}
+
+fn event_loop(
+    terminal: &mut DefaultTerminal,
+    interval: Duration,
+    mut refresh: impl FnMut() -> Result<Vec<PortEntry>>,
+    policy: &ProtectionPolicy,
+) -> Result<()> {
+    let mut app = App::new();
+    app.set_entries(refresh()?);
+    let mut last_refresh = Instant::now();
+
+    loop {
+        update_children(&mut app);
+        terminal.draw(|frame| ui::draw(frame, &mut app))?;
+
+        let mut refresh_now = last_refresh.elapsed() >= interval;
+        if !refresh_now && event::poll(interval.saturating_sub(last_refresh.elapsed()))? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press {
+                    match app.handle_key(key) {
+                        Action::None => {}
+                        Action::Quit => return Ok(()),
+                        Action::Refresh => refresh_now = true,
+                        Action::Kill { pid } => {
+                            app.status = Some(stop_selected(&app, pid, policy));
+                            refresh_now = true;
+                        }
+                    }
+                }
+            }
+        }
+
+        if refresh_now {
+            // A failed scan keeps the previous list on screen
+            match refresh() {
+                Ok(entries) => app.set_entries(entries),
+                Err(e) => app.status = Some(format!("更新に失敗しました: {}", e)),
+            }
+            last_refresh = Instant::now();
+        }
+    }
+}
+
+/// Looks up the children of the selected process if they are not known
+/// yet.
+fn update_children(app: &mut App) {
+    let Some(pid) = app.selected_entry().map(|e| e.process.pid) else {
+        return;
+    };
+    if app.children.as_ref().is_none_or(|(known, _)| *known != pid) {
+        app.children = Some((pid, process_info::child_processes(pid)));
+    }
+}
+
+/// Sends SIGTERM to the selected process unless it is protected, and
+/// returns the message to show.
+///
+/// Unlike `ports kill` this does not wait for the port to be released;
+/// the refresh that follows shows whether it was.
+fn stop_selected(app: &App, pid: u32, policy: &ProtectionPolicy) -> String {
+    let Some(entry) = app.visible_entries().find(|e| e.process.pid == pid) else {
+        return format!("PID {} は見つかりません", pid);
+    };
+
+    if let Err(e) = policy.check_permission(&entry.process) {
+        return e.to_string();
+    }
+    if let Some(protection) = policy.check(entry) {
+        let error = PortsError::protected(pid, &entry.process.name, protection.reason());
+        return format!("{}. {}", error, error.hint());
+    }
+
+    match kill::send_signal(pid, Signal::TERM) {
+        Ok(true) => format!(
+            "PID {} ({}) に SIGTERM を送信しました",
+            pid, entry.process.name
+        ),
+        Ok(false) => format!("PID {} ({}) は既に終了しています", pid, entry.process.name),
+        Err(e) => e.to_string(),
+    }
+}
@@ -0,0 +1,425 @@
+//! State of the interactive port browser and its key bindings.
+
+use crate::models::PortEntry;
+use crate::sort::{self, SortKey};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::widgets::TableState;
+
+/// Number of rows moved by PageUp and PageDown.
+const PAGE_SIZE: usize = 10;
+
+/// Keys the list can be sorted by, in the order `s` cycles through them.
+const SORT_KEYS: [SortKey; 6] = [
+    SortKey::Port,
+    SortKey::Pid,
+    SortKey::Type,
+    SortKey::Project,
+    SortKey::Uptime,
+    SortKey::Command,
+];
+
+/// Returns the sort key following `key` in [`SORT_KEYS`].
+fn next_sort_key(key: SortKey) -> SortKey {
+    let index = SORT_KEYS.iter().position(|&k| k == key).unwrap_or(0);
+    SORT_KEYS[(index + 1) % SORT_KEYS.len()]
+}
+
+/// Returns the header of the column sorted by `key`.
+pub fn sort_header(key: SortKey) -> &'static str {
+    match key {
+        SortKey::Port => "PORT",
+        SortKey::Pid => "PID",
+        SortKey::Type => "TYPE",
+        SortKey::Project => "PROJECT",
+        SortKey::Uptime => "UPTIME",
+        SortKey::User => "USER",
+        SortKey::Command => "COMMAND",
+    }
+}
+
+/// What the browser is waiting for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mode {
+    /// Browsing the list
+    Normal,
+    /// Typing a search query
+    Search,
+    /// Asking whether to stop a process
+    ConfirmKill { pid: u32, name: String },
+}
+
+/// What the event loop should do after a key press.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    None,
+    Quit,
+    Refresh,
+    Kill { pid: u32 },
+}
+
+/// State of the port browser.
+#[derive(Debug)]
+pub struct App {
+    entries: Vec<PortEntry>,
+    /// Indices into `entries` of the rows shown, in display order
+    visible: Vec<usize>,
+    /// Case-insensitive search query
+    pub query: String,
+    pub mode: Mode,
+    pub sort: SortKey,
+    pub reverse: bool,
+    pub table_state: TableState,
+    /// Message shown in the status bar until the next key press
+    pub status: Option<String>,
+    /// Children of the selected process, keyed by its PID
+    pub children: Option<(u32, Vec<(u32, String)>)>,
+}
+
+impl App {
+    /// Creates a browser with no entries.
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            visible: Vec::new(),
+            query: String::new(),
+            mode: Mode::Normal,
+            sort: SortKey::Port,
+            reverse: false,
+            table_state: TableState::default(),
+            status: None,
+            children: None,
+        }
+    }
+
+    /// Replaces the entries, keeping the selected socket selected if it is
+    /// still listening.
+    pub fn set_entries(&mut self, entries: Vec<PortEntry>) {
+        let selected = self
+            .selected_entry()
+            .map(|e| (e.port, e.protocol, e.process.pid));
+        self.entries = entries;
+        self.children = None;
+        self.update_view();
+
+        let index = selected.and_then(|key| {
+            self.visible_entries()
+                .position(|e| (e.port, e.protocol, e.process.pid) == key)
+        });
+        if index.is_some() {
+            self.table_state.select(index);
+        }
+    }
+
+    /// Returns the rows shown, in display order.
+    pub fn visible_entries(&self) -> impl Iterator<Item = &PortEntry> {
+        self.visible.iter().map(|&i| &self.entries[i])
+    }
+
+    /// Returns the number of rows shown and the total number of entries.
+    pub fn counts(&self) -> (usize, usize) {
+        (self.visible.len(), self.entries.len())
+    }
+
+    /// Returns the entry of the selected row.
+    pub fn selected_entry(&self) -> Option<&PortEntry> {
+        let index = *self.visible.get(self.table_state.selected()?)?;
+        self.entries.get(index)
+    }
+
+    /// Handles a key press.
+    pub fn handle_key(&mut self, key: KeyEvent) -> Action {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return Action::Quit;
+        }
+        self.status = None;
+
+        match self.mode.clone() {
+            Mode::Normal => self.handle_normal_key(key),
+            Mode::Search => {
+                self.handle_search_key(key);
+                Action::None
+            }
+            Mode::ConfirmKill { pid, .. } => {
+                self.mode = Mode::Normal;
+                if matches!(key.code, KeyCode::Char('y' | 'Y')) {
+                    Action::Kill { pid }
+                } else {
+                    self.status = Some("中止しました".to_string());
+                    Action::None
+                }
+            }
+        }
+    }
+
+    fn handle_normal_key(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Char('q') => return Action::Quit,
+            KeyCode::Esc if self.query.is_empty() => return Action::Quit,
+            KeyCode::Esc => {
+                self.query.clear();
+                self.update_view();
+            }
+            KeyCode::Char('r') => return Action::Refresh,
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection(1),
+            KeyCode::PageUp => self.move_selection(-(PAGE_SIZE as isize)),
+            KeyCode::PageDown => self.move_selection(PAGE_SIZE as isize),
+            KeyCode::Home | KeyCode::Char('g') => self.select(0),
+            KeyCode::End | KeyCode::Char('G') => self.select(self.visible.len().saturating_sub(1)),
+            KeyCode::Char('/') => self.mode = Mode::Search,
+            KeyCode::Char('s') => {
+                self.sort = next_sort_key(self.sort);
+                self.update_view();
+            }
+            KeyCode::Char('S') => {
+                self.reverse = !self.reverse;
+                self.update_view();
+            }
+            KeyCode::Char('x') => {
+                if let Some(entry) = self.selected_entry() {
+                    self.mode = Mode::ConfirmKill {
+                        pid: entry.process.pid,
+                        name: entry.process.name.clone(),
+                    };
+                }
+            }
+            _ => {}
+        }
+        Action::None
+    }
+
+    fn handle_search_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Enter => self.mode = Mode::Normal,
+            KeyCode::Esc => {
+                self.mode = Mode::Normal;
+                self.query.clear();
+            }
+            KeyCode::Backspace => {
+                self.query.pop();
+            }
+            KeyCode::Char(c) => self.query.push(c),
+            _ => return,
+        }
+        // The list narrows while typing
+        self.update_view();
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        let current = self.table_state.selected().unwrap_or(0);
+        self.select(current.saturating_add_signed(delta));
+    }
+
+    fn select(&mut self, index: usize) {
+        if self.visible.is_empty() {
+            self.table_state.select(None);
+        } else {
+            self.table_state
+                .select(Some(index.min(self.visible.len() - 1)));
+        }
+    }
+
+    /// Recomputes the rows shown from the query and sort order.
+    fn update_view(&mut self) {
+        let query = self.query.to_lowercase();
+        let mut visible: Vec<usize> = (0..self.entries.len())
+            .filter(|&i| matches_query(&self.entries[i], &query))
+            .collect();
+
+        // Same order as --sort; ties keep the port order of the scan
+        visible.sort_by(|&a, &b| {
+            sort::compare_entries(
+                &self.entries[a],
+                &self.entries[b],
+                &[self.sort],
+                self.reverse,
+                None,
+            )
+        });
+        self.visible = visible;
+
+        let selected = self.table_state.selected().unwrap_or(0);
+        self.select(selected);
+    }
+}
+
+/// Returns true if an entry contains the lowercase query in any of the
+/// columns shown.
+fn matches_query(entry: &PortEntry, query: &str) -> bool {
+    if query.is_empty() {
+        return true;
+    }
+
+    let process = &entry.process;
+    [
+        Some(entry.port.to_string()),
+        Some(entry.protocol.to_string()),
+        Some(process.pid.to_string()),
+        Some(process.name.clone()),
+        process.command.clone(),
+        process.user.clone(),
+        process.project_name.clone(),
+        Some(entry.app_type_display()),
+        entry.framework.clone(),
+        entry.expected_service.clone(),
+    ]
+    .into_iter()
+    .flatten()
+    .any(|field| field.to_lowercase().contains(query))
+}
+
+/// Returns the command line of an entry, or the process name.
+pub fn command_of(entry: &PortEntry) -> &str {
+    entry
+        .process
+        .command
+        .as_deref()
+        .unwrap_or(&entry.process.name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{AppType, ProcessInfo, Protocol};
+    use std::time::Duration;
+
+    fn entry(port: u16, pid: u32, name: &str, app_type: AppType, uptime: u64) -> PortEntry {
+        let mut process = ProcessInfo::new(pid, name);
+        process.elapsed = Some(Duration::from_secs(uptime));
+        PortEntry::new(port, Protocol::Tcp, process).with_app_type(app_type)
+    }
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.set_entries(vec![
+            entry(3000, 300, "node", AppType::NodeJs, 60),
+            entry(5432, 100, "postgres", AppType::PostgreSql, 3600),
+            entry(8000, 200, "python3", AppType::Python, 5),
+        ]);
+        app
+    }
+
+    fn press(app: &mut App, code: KeyCode) -> Action {
+        app.handle_key(KeyEvent::from(code))
+    }
+
+    fn ports(app: &App) -> Vec<u16> {
+        app.visible_entries().map(|e| e.port).collect()
+    }
+
+    #[test]
+    fn test_navigation() {
+        let mut app = app();
+        assert_eq!(app.table_state.selected(), Some(0));
+
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Char('j'));
+        press(&mut app, KeyCode::Down);
+        assert_eq!(app.selected_entry().map(|e| e.port), Some(8000));
+
+        press(&mut app, KeyCode::Char('g'));
+        assert_eq!(app.selected_entry().map(|e| e.port), Some(3000));
+        press(&mut app, KeyCode::End);
+        assert_eq!(app.selected_entry().map(|e| e.port), Some(8000));
+    }
+
+    #[test]
+    fn test_incremental_search() {
+        let mut app = app();
+        press(&mut app, KeyCode::Char('/'));
+        assert_eq!(app.mode, Mode::Search);
+
+        press(&mut app, KeyCode::Char('n'));
+        assert_eq!(ports(&app), vec![3000, 8000]);
+        press(&mut app, KeyCode::Char('O'));
+        assert_eq!(ports(&app), vec![3000]);
+        press(&mut app, KeyCode::Backspace);
+        assert_eq!(ports(&app), vec![3000, 8000]);
+
+        // Enter keeps the filter, Esc clears it
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.mode, Mode::Normal);
+        assert_eq!(ports(&app), vec![3000, 8000]);
+        assert_eq!(press(&mut app, KeyCode::Esc), Action::None);
+        assert_eq!(ports(&app), vec![3000, 5432, 8000]);
+        assert_eq!(press(&mut app, KeyCode::Esc), Action::Quit);
+    }
+
+    #[test]
+    fn test_sort() {
+        let mut app = app();
+        press(&mut app, KeyCode::Char('s'));
+        assert_eq!(app.sort, SortKey::Pid);
+        assert_eq!(ports(&app), vec![5432, 8000, 3000]);
+
+        press(&mut app, KeyCode::Char('S'));
+        assert_eq!(ports(&app), vec![3000, 8000, 5432]);
+
+        for _ in 0..3 {
+            press(&mut app, KeyCode::Char('s'));
+        }
+        assert_eq!(app.sort, SortKey::Uptime);
+        assert_eq!(ports(&app), vec![5432, 3000, 8000]);
+
+        // Unknown values come last, as with --sort
+        let mut unknown = entry(1234, 400, "mystery", AppType::Unknown, 0);
+        unknown.process.elapsed = None;
+        let mut entries = app.entries.clone();
+        entries.push(unknown);
+        app.set_entries(entries);
+        press(&mut app, KeyCode::Char('S'));
+        assert_eq!(ports(&app), vec![8000, 3000, 5432, 1234]);
+
+        app.sort = SortKey::Type;
+        app.set_entries(app.entries.clone());
+        assert_eq!(ports(&app), vec![3000, 5432, 8000, 1234]);
+    }
+
+    #[test]
+    fn test_kill_confirmation() {
+        let mut app = app();
+        press(&mut app, KeyCode::Down);
+
+        assert_eq!(press(&mut app, KeyCode::Char('x')), Action::None);
+        assert_eq!(
+            app.mode,
+            Mode::ConfirmKill {
+                pid: 100,
+                name: "postgres".to_string()
+            }
+        );
+        assert_eq!(
+            press(&mut app, KeyCode::Char('y')),
+            Action::Kill { pid: 100 }
+        );
+        assert_eq!(app.mode, Mode::Normal);
+
+        press(&mut app, KeyCode::Char('x'));
+        assert_eq!(press(&mut app, KeyCode::Char('n')), Action::None);
+        assert_eq!(app.status.as_deref(), Some("中止しました"));
+    }
+
+    #[test]
+    fn test_selection_follows_refresh() {
+        let mut app = app();
+        press(&mut app, KeyCode::Down);
+        assert_eq!(app.selected_entry().map(|e| e.port), Some(5432));
+
+        // A new port opened before the selected one
+        app.set_entries(vec![
+            entry(22, 1, "sshd", AppType::Unknown, 9999),
+            entry(3000, 300, "node", AppType::NodeJs, 60),
+            entry(5432, 100, "postgres", AppType::PostgreSql, 3600),
+        ]);
+        assert_eq!(app.selected_entry().map(|e| e.port), Some(5432));
+
+        // The selected port closed
+        app.set_entries(vec![entry(22, 1, "sshd", AppType::Unknown, 9999)]);
+        assert_eq!(app.selected_entry().map(|e| e.port), Some(22));
+
+        app.set_entries(Vec::new());
+        assert_eq!(app.selected_entry().map(|e| e.port), None);
+        assert_eq!(press(&mut app, KeyCode::Char('x')), Action::None);
+        assert_eq!(app.mode, Mode::Normal);
+    }
+}
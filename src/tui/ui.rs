@@ -0,0 +1,333 @@
+//! Rendering of the port browser.
+//!
+//! The screen is split into the port list, a detail pane for the selected
+//! process and a one-line status bar.
+
+use super::app::{command_of, sort_header, App, Mode};
+use crate::models::PortEntry;
+use chrono::Local;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Cell, Paragraph, Row, Table, Wrap};
+use ratatui::Frame;
+
+/// Height of the detail pane, including its border.
+const DETAIL_HEIGHT: u16 = 12;
+
+/// Width of the labels in the detail pane.
+const LABEL_WIDTH: usize = 18;
+
+/// Key bindings shown in the status bar.
+const KEY_HELP: &str = "↑↓/jk 移動  / 検索  s 並べ替え  S 逆順  x 停止  r 更新  q 終了";
+
+/// Draws the whole screen.
+pub fn draw(frame: &mut Frame, app: &mut App) {
+    let [list, detail, status] = Layout::vertical([
+        Constraint::Min(5),
+        Constraint::Length(DETAIL_HEIGHT),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    draw_list(frame, app, list);
+    draw_detail(frame, app, detail);
+    draw_status(frame, app, status);
+}
+
+/// Draws the port list.
+fn draw_list(frame: &mut Frame, app: &mut App, area: Rect) {
+    let widths = [
+        Constraint::Length(7),
+        Constraint::Length(5),
+        Constraint::Length(7),
+        Constraint::Length(16),
+        Constraint::Length(14),
+        Constraint::Length(8),
+        Constraint::Min(10),
+    ];
+    let headers = [
+        "PORT", "PROTO", "PID", "TYPE", "PROJECT", "UPTIME", "COMMAND",
+    ];
+
+    let arrow = if app.reverse { " ▼" } else { " ▲" };
+    let header = Row::new(headers.iter().map(|&title| {
+        if title == sort_header(app.sort) {
+            Cell::from(format!("{}{}", title, arrow))
+        } else {
+            Cell::from(title)
+        }
+    }))
+    .style(Style::new().add_modifier(Modifier::BOLD));
+
+    let rows: Vec<Row> = app.visible_entries().map(entry_row).collect();
+
+    let (shown, total) = app.counts();
+    let mut title = format!(" ports  {}/{} ", shown, total);
+    if !app.query.is_empty() {
+        title.push_str(&format!(" 検索: {} ", app.query));
+    }
+
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(Block::bordered().title(title))
+        .row_highlight_style(Style::new().reversed())
+        .highlight_symbol("> ");
+
+    frame.render_stateful_widget(table, area, &mut app.table_state);
+}
+
+/// Returns the table row of an entry.
+fn entry_row(entry: &PortEntry) -> Row<'static> {
+    let process = &entry.process;
+    Row::new([
+        entry.port.to_string(),
+        entry.protocol.to_string(),
+        process.pid.to_string(),
+        entry.app_type_display(),
+        process
+            .project_name
+            .clone()
+            .unwrap_or_else(|| "-".to_string()),
+        process.elapsed_human().unwrap_or_else(|| "-".to_string()),
+        command_of(entry).to_string(),
+    ])
+}
+
+/// Draws the details of the selected process.
+fn draw_detail(frame: &mut Frame, app: &App, area: Rect) {
+    let block = Block::bordered().title(" 詳細 ");
+    let Some(entry) = app.selected_entry() else {
+        frame.render_widget(
+            Paragraph::new("ポートが選択されていません").block(block),
+            area,
+        );
+        return;
+    };
+
+    let children = app
+        .children
+        .as_ref()
+        .filter(|(pid, _)| *pid == entry.process.pid)
+        .map(|(_, children)| children.as_slice());
+
+    let paragraph = Paragraph::new(detail_lines(entry, children))
+        .block(block)
+        .wrap(Wrap { trim: false });
+    frame.render_widget(paragraph, area);
+}
+
+/// Returns the lines of the detail pane.
+///
+/// `children` is `None` while the children have not been looked up.
+fn detail_lines(entry: &PortEntry, children: Option<&[(u32, String)]>) -> Vec<Line<'static>> {
+    let process = &entry.process;
+    let or_dash = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+    let field = |label: &str, value: String| {
+        Line::from(vec![
+            Span::styled(pad_label(label), Style::new().bold()),
+            Span::raw(value),
+        ])
+    };
+
+    let mut app_type = entry.app_type_display();
+    if let Some(framework) = &entry.framework {
+        app_type.push_str(&format!(" / {}", framework));
+    }
+    if let Some(detection) = &entry.detection {
+        if let Some(rule) = &detection.matched_rule {
+            app_type.push_str(&format!(
+                "  (ルール: {}, 確度: {:?})",
+                rule, detection.confidence
+            ));
+        }
+    }
+
+    let user = match (&process.user, process.uid) {
+        (Some(user), Some(uid)) => format!("{} (uid {})", user, uid),
+        (Some(user), None) => user.clone(),
+        (None, Some(uid)) => uid.to_string(),
+        (None, None) => "-".to_string(),
+    };
+
+    let started = process.started_at.map(|started| {
+        format!(
+            "{}  ({})",
+            started.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S"),
+            or_dash(process.elapsed_human())
+        )
+    });
+
+    let project = process
+        .project_name
+        .as_ref()
+        .map(|name| match &process.project_dir {
+            Some(dir) => format!("{} ({})", name, dir.display()),
+            None => name.clone(),
+        });
+
+    let children = match children {
+        None => "…".to_string(),
+        Some([]) => "なし".to_string(),
+        Some(children) => children
+            .iter()
+            .map(|(pid, name)| format!("{} {}", pid, name))
+            .collect::<Vec<_>>()
+            .join(", "),
+    };
+
+    vec![
+        field(
+            "PID",
+            format!(
+                "{} ({})  親: {}",
+                process.pid,
+                process.name,
+                or_dash(process.ppid.map(|ppid| ppid.to_string()))
+            ),
+        ),
+        field("コマンド", command_of(entry).to_string()),
+        field(
+            "実行ファイル",
+            or_dash(process.exe.as_ref().map(|exe| exe.display().to_string())),
+        ),
+        field("ユーザー", user),
+        field("開始", or_dash(started)),
+        field(
+            "作業ディレクトリ",
+            or_dash(process.cwd.as_ref().map(|cwd| cwd.display().to_string())),
+        ),
+        field("プロジェクト", or_dash(project)),
+        field("種類", app_type),
+        field(
+            "アドレス",
+            format!("{} {}", entry.protocol, entry.address_display()),
+        ),
+        field("子プロセス", children),
+    ]
+}
+
+/// Pads a label of the detail pane to a fixed width, counting full-width
+/// characters as two columns.
+fn pad_label(label: &str) -> String {
+    let width: usize = label
+        .chars()
+        .map(|c| if c.is_ascii() { 1 } else { 2 })
+        .sum();
+    format!("{}{}", label, " ".repeat(LABEL_WIDTH.saturating_sub(width)))
+}
+
+/// Draws the status bar: the search prompt, the kill confirmation, the
+/// last message or the key bindings.
+fn draw_status(frame: &mut Frame, app: &App, area: Rect) {
+    let line = match &app.mode {
+        Mode::Search => Line::from(format!("/{}█", app.query)),
+        Mode::ConfirmKill { pid, name } => Line::from(format!(
+            "PID {} ({}) に SIGTERM を送信しますか? [y/N]",
+            pid, name
+        ))
+        .yellow()
+        .bold(),
+        Mode::Normal => match &app.status {
+            Some(status) => Line::from(status.clone()).yellow(),
+            None => Line::from(KEY_HELP).dim(),
+        },
+    };
+    frame.render_widget(Paragraph::new(line), area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{AppType, ProcessInfo, Protocol};
+    use crossterm::event::{KeyCode, KeyEvent};
+    use ratatui::backend::TestBackend;
+    use ratatui::buffer::CellWidth;
+    use ratatui::Terminal;
+    use std::path::PathBuf;
+
+    fn render(app: &mut App) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(120, 30)).unwrap();
+        terminal.draw(|frame| draw(frame, app)).unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let mut text = String::new();
+        for y in 0..buffer.area.height {
+            // Full-width characters are followed by a blank cell
+            let mut skip: u16 = 0;
+            for x in 0..buffer.area.width {
+                let cell = &buffer[(x, y)];
+                if skip == 0 {
+                    text.push_str(cell.symbol());
+                }
+                skip = skip.max(cell.cell_width()).saturating_sub(1);
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    fn app() -> App {
+        let mut node = ProcessInfo::new(4242, "node");
+        node.command = Some("node server.js --port 3000".to_string());
+        node.user = Some("alice".to_string());
+        node.uid = Some(501);
+        node.ppid = Some(4000);
+        node.cwd = Some(PathBuf::from("/home/alice/web"));
+        node.project_name = Some("web".to_string());
+
+        let mut app = App::new();
+        app.set_entries(vec![
+            PortEntry::new(3000, Protocol::Tcp, node).with_app_type(AppType::NodeJs),
+            PortEntry::new(5432, Protocol::Tcp, ProcessInfo::new(99, "postgres"))
+                .with_app_type(AppType::PostgreSql),
+        ]);
+        app
+    }
+
+    #[test]
+    fn test_render_list_and_detail() {
+        let mut app = app();
+        app.children = Some((4242, vec![(4300, "esbuild".to_string())]));
+        let screen = render(&mut app);
+
+        assert!(screen.contains("ports  2/2"));
+        assert!(screen.contains("PORT ▲"));
+        assert!(screen.contains("postgres"));
+        // Detail pane of the selected node process
+        assert!(screen.contains("node server.js --port 3000"));
+        assert!(screen.contains("alice (uid 501)"));
+        assert!(screen.contains("/home/alice/web"));
+        assert!(screen.contains("4300 esbuild"));
+        assert!(screen.contains("q 終了"));
+    }
+
+    #[test]
+    fn test_render_search_and_confirmation() {
+        let mut app = app();
+        for code in [
+            KeyCode::Char('/'),
+            KeyCode::Char('g'),
+            KeyCode::Char('r'),
+            KeyCode::Char('e'),
+        ] {
+            app.handle_key(KeyEvent::from(code));
+        }
+        let screen = render(&mut app);
+        assert!(screen.contains("ports  1/2  検索: gre"));
+        assert!(screen.contains("/gre█"));
+
+        app.handle_key(KeyEvent::from(KeyCode::Enter));
+        app.handle_key(KeyEvent::from(KeyCode::Char('x')));
+        let screen = render(&mut app);
+        assert!(screen.contains("PID 99 (postgres) に SIGTERM を送信しますか? [y/N]"));
+    }
+
+    #[test]
+    fn test_render_empty() {
+        let mut app = App::new();
+        let screen = render(&mut app);
+        assert!(screen.contains("ports  0/0"));
+        assert!(screen.contains("ポートが選択されていません"));
+    }
+}
@@ -0,0 +1,171 @@
+//! Change tracking for `--watch`.
+//!
+//! Each tick re-runs the scan and compares the result with the previous
+//! tick. Sockets are identified by port, protocol and bind address, so a
+//! restarted server shows up as a change of owner rather than as a closed
+//! and a reopened port.
+
+use crate::models::{PortEntry, ProcessInfo};
+use serde::Serialize;
+
+/// How a listening socket changed between two ticks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    /// A socket started listening
+    Opened,
+    /// A socket stopped listening
+    Closed,
+    /// The socket is now held by a different process
+    OwnerChanged,
+}
+
+/// A change to a listening socket.
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangeEvent {
+    /// What changed
+    pub event: ChangeKind,
+    /// The socket as seen now, or as last seen if it was closed
+    #[serde(flatten)]
+    pub entry: PortEntry,
+    /// The process that held the socket before its owner changed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_process: Option<ProcessInfo>,
+}
+
+impl ChangeEvent {
+    fn new(event: ChangeKind, entry: &PortEntry) -> Self {
+        Self {
+            event,
+            entry: entry.clone(),
+            previous_process: None,
+        }
+    }
+}
+
+/// Returns the changes from `previous` to `current`, in port order.
+pub fn diff(previous: &[PortEntry], current: &[PortEntry]) -> Vec<ChangeEvent> {
+    let unchanged = |entry: &PortEntry, others: &[PortEntry]| {
+        others
+            .iter()
+            .any(|other| same_socket(entry, other) && same_owner(entry, other))
+    };
+
+    let mut closed: Vec<&PortEntry> = previous
+        .iter()
+        .filter(|entry| !unchanged(entry, current))
+        .collect();
+
+    let mut events = Vec::new();
+    for entry in current.iter().filter(|entry| !unchanged(entry, previous)) {
+        // A socket that lost its owner and gained a new one in the same
+        // tick changed hands
+        match closed.iter().position(|old| same_socket(old, entry)) {
+            Some(index) => {
+                let old = closed.remove(index);
+                events.push(ChangeEvent {
+                    previous_process: Some(old.process.clone()),
+                    ..ChangeEvent::new(ChangeKind::OwnerChanged, entry)
+                });
+            }
+            None => events.push(ChangeEvent::new(ChangeKind::Opened, entry)),
+        }
+    }
+    events.extend(
+        closed
+            .into_iter()
+            .map(|entry| ChangeEvent::new(ChangeKind::Closed, entry)),
+    );
+
+    // Stable sort keeps opened sockets before closed ones on the same port
+    events.sort_by_key(|e| (e.entry.port, e.entry.protocol));
+    events
+}
+
+/// Returns true if two entries describe the same socket.
+pub fn same_socket(a: &PortEntry, b: &PortEntry) -> bool {
+    a.port == b.port && a.protocol == b.protocol && a.bind_address == b.bind_address
+}
+
+/// Returns true if two entries are held by the same process.
+///
+/// The start time tells a reused PID apart from the original process.
+pub fn same_owner(a: &PortEntry, b: &PortEntry) -> bool {
+    a.process.pid == b.process.pid && a.process.started_at == b.process.started_at
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Protocol;
+
+    fn entry(port: u16, protocol: Protocol, pid: u32) -> PortEntry {
+        PortEntry::new(port, protocol, ProcessInfo::new(pid, "test"))
+            .with_bind_address("127.0.0.1".parse().unwrap())
+    }
+
+    fn kinds(events: &[ChangeEvent]) -> Vec<(u16, ChangeKind)> {
+        events.iter().map(|e| (e.entry.port, e.event)).collect()
+    }
+
+    #[test]
+    fn test_diff() {
+        let previous = vec![
+            entry(3000, Protocol::Tcp, 10),
+            entry(5432, Protocol::Tcp, 20),
+            entry(8080, Protocol::Tcp, 30),
+        ];
+        let current = vec![
+            entry(3000, Protocol::Tcp, 10),
+            entry(5173, Protocol::Tcp, 40),
+            entry(8080, Protocol::Tcp, 31),
+        ];
+
+        let events = diff(&previous, &current);
+        assert_eq!(
+            kinds(&events),
+            vec![
+                (5173, ChangeKind::Opened),
+                (5432, ChangeKind::Closed),
+                (8080, ChangeKind::OwnerChanged),
+            ]
+        );
+        assert_eq!(events[2].entry.process.pid, 31);
+        assert_eq!(events[2].previous_process.as_ref().map(|p| p.pid), Some(30));
+    }
+
+    #[test]
+    fn test_diff_distinguishes_sockets() {
+        let mut ipv6 = entry(3000, Protocol::Tcp, 10);
+        ipv6.bind_address = Some("::1".parse().unwrap());
+
+        let events = diff(
+            &[entry(3000, Protocol::Tcp, 10)],
+            &[entry(3000, Protocol::Udp, 10), ipv6],
+        );
+        assert_eq!(
+            events.iter().map(|e| e.event).collect::<Vec<_>>(),
+            vec![ChangeKind::Opened, ChangeKind::Closed, ChangeKind::Opened]
+        );
+    }
+
+    #[test]
+    fn test_diff_reused_pid() {
+        let mut restarted = entry(3000, Protocol::Tcp, 10);
+        restarted.process.started_at = Some(chrono::Utc::now());
+
+        let events = diff(&[entry(3000, Protocol::Tcp, 10)], &[restarted]);
+        assert_eq!(kinds(&events), vec![(3000, ChangeKind::OwnerChanged)]);
+    }
+
+    #[test]
+    fn test_diff_first_tick_and_no_changes() {
+        let current = vec![entry(22, Protocol::Tcp, 1), entry(53, Protocol::Udp, 2)];
+
+        assert_eq!(
+            kinds(&diff(&[], &current)),
+            vec![(22, ChangeKind::Opened), (53, ChangeKind::Opened)]
+        );
+        assert!(diff(&current, &current).is_empty());
+    }
+}
@@ -0,0 +1,3 @@
+//! Test harness for the CLI integration tests in `tests/integration`.
+
+mod integration;
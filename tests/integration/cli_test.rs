@@ -1,22 +1,24 @@
 //! Integration tests for CLI commands.
 
-use assert_cmd::Command;
+use assert_cmd::cargo::cargo_bin_cmd;
 use predicates::prelude::*;
 
 /// Test that --help flag displays usage information.
 #[test]
 fn test_help_flag() {
-    let mut cmd = Command::cargo_bin("ports").unwrap();
+    let mut cmd = cargo_bin_cmd!("ports");
     cmd.arg("--help")
         .assert()
         .success()
-        .stdout(predicate::str::contains("リッスン中のTCPポートとアプリケーション情報を表示"));
+        .stdout(predicate::str::contains(
+            "リッスン中のTCP/UDPポートとアプリケーション情報を表示",
+        ));
 }
 
 /// Test that --version flag displays version.
 #[test]
 fn test_version_flag() {
-    let mut cmd = Command::cargo_bin("ports").unwrap();
+    let mut cmd = cargo_bin_cmd!("ports");
     cmd.arg("--version")
         .assert()
         .success()
@@ -26,7 +28,7 @@ fn test_version_flag() {
 /// Test that invalid flag returns error.
 #[test]
 fn test_invalid_flag() {
-    let mut cmd = Command::cargo_bin("ports").unwrap();
+    let mut cmd = cargo_bin_cmd!("ports");
     cmd.arg("--invalid-flag")
         .assert()
         .failure()
@@ -36,14 +38,14 @@ fn test_invalid_flag() {
 /// Test basic execution (may show ports or empty message).
 #[test]
 fn test_basic_execution() {
-    let mut cmd = Command::cargo_bin("ports").unwrap();
+    let mut cmd = cargo_bin_cmd!("ports");
     cmd.assert().success();
 }
 
 /// Test JSON output format.
 #[test]
 fn test_json_output() {
-    let mut cmd = Command::cargo_bin("ports").unwrap();
+    let mut cmd = cargo_bin_cmd!("ports");
     cmd.arg("--json")
         .assert()
         .success()
@@ -55,9 +57,189 @@ fn test_json_output() {
 /// Test short JSON flag.
 #[test]
 fn test_short_json_flag() {
-    let mut cmd = Command::cargo_bin("ports").unwrap();
+    let mut cmd = cargo_bin_cmd!("ports");
     cmd.arg("-j")
         .assert()
         .success()
         .stdout(predicate::str::contains("\"ports\""));
 }
+
+/// Test that an explicit scanner backend is accepted.
+#[test]
+fn test_backend_option() {
+    let mut cmd = cargo_bin_cmd!("ports");
+    cmd.args(["--backend", "lsof", "--json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"ports\""));
+}
+
+/// Test that an unknown scanner backend is rejected.
+#[test]
+fn test_invalid_backend() {
+    let mut cmd = cargo_bin_cmd!("ports");
+    cmd.args(["--backend", "carrier-pigeon"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid value"));
+}
+
+/// Test that protocol filters are accepted.
+#[test]
+fn test_protocol_filters() {
+    let mut cmd = cargo_bin_cmd!("ports");
+    cmd.args(["--udp", "--json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"Tcp\"").not());
+}
+
+/// Test that audit subcommand produces JSON with summary counts.
+#[test]
+fn test_audit_json_output() {
+    let mut cmd = cargo_bin_cmd!("ports");
+    let output = cmd
+        .args(["audit", "--json", "--never-public", "1"])
+        .output()
+        .unwrap();
+
+    // Exit status 3 means violations were found, which depends on the host
+    assert!(matches!(output.status.code(), Some(0) | Some(3)));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("violation_count"));
+    assert!(stdout.contains("findings"));
+}
+
+/// Test that invalid never-public ports are rejected.
+#[test]
+fn test_audit_invalid_port_list() {
+    let mut cmd = cargo_bin_cmd!("ports");
+    cmd.args(["audit", "--never-public", "http"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("error"));
+}
+
+/// Test that --explain is accepted.
+#[test]
+fn test_explain_flag() {
+    let mut cmd = cargo_bin_cmd!("ports");
+    cmd.arg("--explain").assert().success();
+}
+
+/// Test that kill reports a port nobody listens on.
+#[test]
+fn test_kill_unused_port() {
+    let mut cmd = cargo_bin_cmd!("ports");
+    cmd.args(["kill", "1", "--dry-run"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains(
+            "No process is listening on port 1",
+        ));
+}
+
+/// Test that kill rejects unknown signals.
+#[test]
+fn test_kill_invalid_signal() {
+    let mut cmd = cargo_bin_cmd!("ports");
+    cmd.args(["kill", "3000", "--signal", "STOP"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("unknown signal"));
+}
+
+/// Test that --interval requires --watch.
+#[test]
+fn test_interval_requires_watch() {
+    let mut cmd = cargo_bin_cmd!("ports");
+    cmd.args(["--interval", "1s"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("--watch"));
+}
+
+/// Test that the tui subcommand is available.
+#[test]
+fn test_tui_help() {
+    let mut cmd = cargo_bin_cmd!("ports");
+    cmd.args(["tui", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--interval"));
+}
+
+/// Test that filters narrow down the JSON output and its count.
+#[test]
+fn test_json_filtered_by_port() {
+    let mut cmd = cargo_bin_cmd!("ports");
+    cmd.args(["--json", "--port", "1", "--range", "1-1023"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"total_count\": 0"));
+}
+
+/// Test that invalid port ranges are rejected.
+#[test]
+fn test_invalid_port_range() {
+    let mut cmd = cargo_bin_cmd!("ports");
+    cmd.args(["--range", "4000-3000"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("invalid port range"));
+}
+
+/// Test that a requested backend that is missing falls back with a warning.
+#[cfg(target_os = "linux")]
+#[test]
+fn test_unavailable_backend() {
+    let mut cmd = cargo_bin_cmd!("ports");
+    cmd.args(["--backend", "lsof"])
+        .env("PATH", "")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "The lsof backend is not available on this system",
+        ));
+}
+
+/// Test that invalid queries are reported with their position.
+#[test]
+fn test_invalid_query() {
+    let mut cmd = cargo_bin_cmd!("ports");
+    cmd.args(["--where", "port in 4000..3000"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("Invalid query"))
+        .stderr(predicate::str::contains("at column 9"));
+}
+
+/// Test that unknown sort keys are rejected.
+#[test]
+fn test_invalid_sort_key() {
+    let mut cmd = cargo_bin_cmd!("ports");
+    cmd.args(["--sort", "type,size"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("invalid value 'size'"));
+}
+
+/// Test that unknown columns are rejected with the list of columns.
+#[test]
+fn test_invalid_column() {
+    let mut cmd = cargo_bin_cmd!("ports");
+    cmd.args(["--columns", "port,size"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("unknown column 'size'"));
+}
+
+/// Test that --no-header omits the table header.
+#[test]
+fn test_no_header() {
+    let mut cmd = cargo_bin_cmd!("ports");
+    cmd.args(["--columns", "port,pid", "--no-header"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("PORT").not());
+}